@@ -0,0 +1,90 @@
+use compiler_project_tc3002_b::{
+    extract_tokens, extract_tokens_borrowed, extract_tokens_filtered, extract_tokens_with_spans,
+};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Wraps the system allocator to count how many allocations are made
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made while running `f`
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Builds a source file of roughly `size` bytes by repeating a small program
+fn generate_source(size: usize) -> String {
+    const SNIPPET: &str = "fn update_total while counter <= 1000 \
+                           total += counter * 2 - -15 ratio = total / 3.75 \
+                           if ratio >= 10.5 && ! done counter += 1 else done = 1\n";
+
+    SNIPPET.repeat(size / SNIPPET.len() + 1)
+}
+
+/// Builds a source file of roughly `size` bytes made only of identifiers
+fn generate_identifiers(size: usize) -> String {
+    const SNIPPET: &str = "alpha beta gamma delta epsilon zeta eta theta iota kappa\n";
+
+    SNIPPET.repeat(size / SNIPPET.len() + 1)
+}
+
+fn bench_lexer(c: &mut Criterion) {
+    let source = generate_source(1 << 20);
+
+    let mut group = c.benchmark_group("lexer");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("extract_tokens", |b| {
+        b.iter(|| extract_tokens(black_box(source.clone())))
+    });
+    group.bench_function("extract_tokens_with_spans", |b| {
+        b.iter(|| extract_tokens_with_spans(black_box(&source)))
+    });
+    group.bench_function("extract_tokens_filtered", |b| {
+        b.iter(|| extract_tokens_filtered(black_box(&source)))
+    });
+    group.finish();
+}
+
+fn bench_identifiers(c: &mut Criterion) {
+    let source = generate_identifiers(1 << 16);
+
+    println!(
+        "allocations on {} bytes of identifiers: owned = {}, borrowed = {}",
+        source.len(),
+        count_allocations(|| extract_tokens_with_spans(&source)),
+        count_allocations(|| extract_tokens_borrowed(&source)),
+    );
+
+    let mut group = c.benchmark_group("identifiers");
+    group.throughput(Throughput::Bytes(source.len() as u64));
+    group.bench_function("owned", |b| {
+        b.iter(|| extract_tokens_with_spans(black_box(&source)))
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| extract_tokens_borrowed(black_box(&source)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexer, bench_identifiers);
+criterion_main!(benches);
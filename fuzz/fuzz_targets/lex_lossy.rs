@@ -0,0 +1,15 @@
+#![no_main]
+
+use compiler_project_tc3002_b::lex_lossy;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let mut end = 0;
+    for item in lex_lossy(input) {
+        let span = item.span();
+        assert_eq!(span.start, end);
+        assert!(span.end > span.start);
+        end = span.end;
+    }
+    assert_eq!(end, input.len());
+});
@@ -0,0 +1,286 @@
+//! # Batch Lexing
+//!
+//! Lexes several files in one go and collects a `FileReport` for each of them. Directories are
+//! searched recursively for files with one of the accepted extensions, and a file that cannot be
+//! read or lexed is recorded as failed without stopping the rest of the batch.
+//!
+//! Files are independent of each other, so they are lexed on several threads at once.
+
+use crate::{LexError, LexerOptions, TokenKind, decode_source, extract_tokens_with_options};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{fmt, fs, io, panic, thread};
+
+/// Extensions of the files picked up when searching a directory
+pub const DEFAULT_EXTENSIONS: &[&str] = &["txt", "lang"];
+
+/// Represents the reasons why a file in a batch could not be lexed
+#[derive(Debug)]
+pub enum FileError {
+    /// The file or directory could not be read (including files that are not valid UTF-8)
+    Io(io::Error),
+    /// The contents of the file could not be split into tokens
+    Lex(LexError),
+    /// The lexer panicked while processing the file
+    Panic(String),
+}
+
+impl fmt::Display for FileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileError::Io(error) => write!(f, "{}", error),
+            FileError::Lex(error) => write!(f, "{}", error),
+            FileError::Panic(message) => write!(f, "lexer panicked: {}", message),
+        }
+    }
+}
+
+/// Result of lexing a single file
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// Number of tokens of each kind found in the file, excluding whitespace
+    pub token_counts: BTreeMap<TokenKind, usize>,
+    pub errors: Vec<FileError>,
+}
+
+impl FileReport {
+    fn failed(path: &Path, error: FileError) -> Self {
+        FileReport {
+            path: path.to_path_buf(),
+            token_counts: BTreeMap::new(),
+            errors: vec![error],
+        }
+    }
+
+    /// Returns `true` if the file was lexed without errors
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the total number of non-whitespace tokens in the file
+    pub fn token_count(&self) -> usize {
+        self.token_counts.values().sum()
+    }
+}
+
+/// Settings for a batch run
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Extensions of the files picked up when searching a directory
+    pub extensions: Vec<String>,
+    /// Number of files lexed at the same time
+    pub threads: usize,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        BatchOptions {
+            extensions: DEFAULT_EXTENSIONS
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+}
+
+/// Lexes every file in `paths`, searching directories for files with a default extension
+pub fn run_many(paths: &[PathBuf]) -> Vec<FileReport> {
+    run_many_with_options(paths, &BatchOptions::default())
+}
+
+/// Lexes every file in `paths` in parallel, searching directories for files with one of the
+/// extensions in `options`
+///
+/// Files given explicitly are always lexed, whatever their extension. The reports are sorted by
+/// path, so the output does not depend on the order in which the threads finish.
+pub fn run_many_with_options(paths: &[PathBuf], options: &BatchOptions) -> Vec<FileReport> {
+    let (files, unreadable) = expand_paths(paths, &options.extensions);
+    let mut reports: Vec<FileReport> = unreadable
+        .into_iter()
+        .map(|(dir, error)| FileReport::failed(&dir, FileError::Io(error)))
+        .collect();
+
+    reports.extend(lex_files(&files, options.threads));
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    reports
+}
+
+/// Returns the files named by `paths`, replacing each directory with the files inside it (and
+/// its subdirectories) that have one of `extensions`
+///
+/// Files given explicitly are kept whatever their extension. Directories that cannot be read are
+/// returned separately along with the error.
+pub fn expand_paths(
+    paths: &[PathBuf],
+    extensions: &[String],
+) -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>) {
+    let extensions: Vec<&str> = extensions.iter().map(String::as_str).collect();
+    let mut files = Vec::new();
+    let mut unreadable = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            collect_directory(path, &extensions, &mut files, &mut unreadable);
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    (files, unreadable)
+}
+
+/// Adds the matching files inside a directory and its subdirectories to `files`, and the
+/// directories that cannot be read to `unreadable`
+fn collect_directory(
+    dir: &Path,
+    extensions: &[&str],
+    files: &mut Vec<PathBuf>,
+    unreadable: &mut Vec<(PathBuf, io::Error)>,
+) {
+    let entries = fs::read_dir(dir).and_then(|entries| {
+        entries
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<_>>>()
+    });
+
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(error) => {
+            unreadable.push((dir.to_path_buf(), error));
+            return;
+        }
+    };
+
+    for path in entries {
+        if path.is_dir() {
+            collect_directory(&path, extensions, files, unreadable);
+        } else if has_extension(&path, extensions) {
+            files.push(path);
+        }
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.contains(&ext))
+}
+
+/// Lexes the files using up to `threads` worker threads, which take the next file from a shared
+/// counter as soon as they finish the previous one
+fn lex_files(files: &[PathBuf], threads: usize) -> Vec<FileReport> {
+    if threads <= 1 {
+        return files.iter().map(|path| lex_file_isolated(path)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(files.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut reports = Vec::new();
+                    while let Some(path) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        reports.push(lex_file_isolated(path));
+                    }
+                    reports
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("panics are caught for each file"))
+            .collect()
+    })
+}
+
+/// Lexes a single file, turning a panic inside the lexer into an error entry for that file
+fn lex_file_isolated(path: &Path) -> FileReport {
+    panic::catch_unwind(|| lex_file(path)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+
+        FileReport::failed(path, FileError::Panic(message))
+    })
+}
+
+/// Reads and lexes a single file
+pub fn lex_file(path: &Path) -> FileReport {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) => return FileReport::failed(path, FileError::Io(error)),
+    };
+    let contents = match decode_source(&bytes) {
+        Ok(contents) => contents,
+        Err(error) => return FileReport::failed(path, FileError::Lex(error)),
+    };
+
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    match extract_tokens_with_options(contents, &options) {
+        Ok(tokens) => {
+            let mut token_counts = BTreeMap::new();
+            for (token, _) in &tokens {
+                *token_counts.entry(token.kind()).or_insert(0) += 1;
+            }
+
+            FileReport {
+                path: path.to_path_buf(),
+                token_counts,
+                errors: Vec::new(),
+            }
+        }
+        Err(error) => FileReport::failed(path, FileError::Lex(error)),
+    }
+}
+
+/// Formats the reports as a table with one row per file and a column per token kind
+pub fn summary_table(reports: &[FileReport]) -> String {
+    /// Every kind except `Whitespace`, which is not counted
+    const KINDS: [TokenKind; 8] = [
+        TokenKind::Keyword,
+        TokenKind::Identifier,
+        TokenKind::Operator,
+        TokenKind::Integer,
+        TokenKind::Decimal,
+        TokenKind::StringLiteral,
+        TokenKind::InterpolatedString,
+        TokenKind::Punctuation,
+    ];
+
+    let paths: Vec<String> = reports
+        .iter()
+        .map(|report| report.path.display().to_string())
+        .collect();
+    let path_width = paths.iter().map(|p| p.len()).max().unwrap_or(0).max(4);
+
+    let headers = KINDS.map(|kind| format!("{:?}", kind));
+
+    let mut out = format!("{:<path_width$}", "File");
+    for header in &headers {
+        out += &format!(" {:>13}", header);
+    }
+    out += " Status\n";
+
+    for (report, path) in reports.iter().zip(&paths) {
+        out += &format!("{:<path_width$}", path);
+        for (kind, header) in KINDS.iter().zip(&headers) {
+            let width = header.len().max(13);
+            out += &format!(" {:>width$}", report.token_counts.get(kind).unwrap_or(&0));
+        }
+        match report.errors.first() {
+            None => out += " ok\n",
+            Some(error) => out += &format!(" error: {}\n", error),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,237 @@
+//! # Token Cache
+//!
+//! Saves the tokens of a source to disk so they can be loaded instead of lexing the same source
+//! again. A cache file is laid out as:
+//!
+//! | Bytes | Contents                                          |
+//! |-------|---------------------------------------------------|
+//! | 4     | `MAGIC`                                           |
+//! | 2     | `VERSION`, little endian                          |
+//! | 8     | FNV-1a hash of the source, little endian          |
+//! | 8     | Length of the source in bytes, little endian      |
+//! | 4     | Number of tokens, little endian                   |
+//! | ...   | Each token                                        |
+//!
+//! Each token is a tag byte followed by its value. Integers are little endian, and text
+//! (identifiers, strings, decimals as written, and the symbols of keywords, operators and
+//! punctuation) is a `u32` length followed by UTF-8 bytes.
+//!
+//! A cache written for a different source, by a different version of the format, or that is cut
+//! short or otherwise damaged (including interpolations nested deeper than
+//! `MAX_INTERPOLATION_DEPTH`) reads as `Ok(None)`, so the caller lexes the source again.
+
+use crate::{
+    DecimalLit, MAX_INTERPOLATION_DEPTH, StringPart, Token, parse_keyword, parse_operator,
+    parse_punctuation,
+};
+use std::path::Path;
+use std::{fs, io};
+
+/// Bytes every cache file starts with
+pub const MAGIC: [u8; 4] = *b"LXTC";
+
+/// Version of the format, to be increased whenever it changes
+pub const VERSION: u16 = 2;
+
+const INTEGER: u8 = 0;
+const WHITESPACE: u8 = 1;
+const IDENTIFIER: u8 = 2;
+const DECIMAL: u8 = 3;
+const KEYWORD: u8 = 4;
+const OPERATOR: u8 = 5;
+const STRING_LITERAL: u8 = 6;
+const INTERPOLATED_STRING: u8 = 7;
+const PUNCTUATION: u8 = 8;
+
+const LITERAL_PART: u8 = 0;
+const TOKENS_PART: u8 = 1;
+
+/// Writes the tokens of `source` to a cache file at `path`
+pub fn write_token_cache(path: &Path, source: &str, tokens: &[Token]) -> io::Result<()> {
+    let mut out = Vec::new();
+    out.extend(MAGIC);
+    out.extend(VERSION.to_le_bytes());
+    out.extend(fnv1a(source.as_bytes()).to_le_bytes());
+    out.extend((source.len() as u64).to_le_bytes());
+    encode_tokens(tokens, &mut out);
+
+    fs::write(path, out)
+}
+
+/// Reads the tokens cached at `path`, or returns `None` if there is no usable cache for `source`
+///
+/// Only errors other than a missing file are returned, so a cache that was never written reads as
+/// `Ok(None)`.
+pub fn read_token_cache(path: &Path, source: &str) -> io::Result<Option<Vec<Token>>> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    Ok(decode(&bytes, source))
+}
+
+/// Returns the tokens of a cache file if it is intact and was written for `source`
+fn decode(bytes: &[u8], source: &str) -> Option<Vec<Token>> {
+    let mut reader = Reader(bytes);
+    let header_matches = reader.take(4)? == MAGIC
+        && reader.u16()? == VERSION
+        && reader.u64()? == fnv1a(source.as_bytes())
+        && reader.u64()? == source.len() as u64;
+    if !header_matches {
+        return None;
+    }
+
+    let tokens = reader.tokens(0)?;
+    reader.0.is_empty().then_some(tokens)
+}
+
+/// 64-bit FNV-1a hash, which unlike the hashers of `std` is the same on every platform and
+/// version of Rust
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn encode_tokens(tokens: &[Token], out: &mut Vec<u8>) {
+    out.extend((tokens.len() as u32).to_le_bytes());
+
+    for token in tokens {
+        match token {
+            Token::Integer(value) => {
+                out.push(INTEGER);
+                out.extend(value.to_le_bytes());
+            }
+            Token::Whitespace => out.push(WHITESPACE),
+            Token::Identifier(name) => {
+                out.push(IDENTIFIER);
+                encode_str(name, out);
+            }
+            Token::Decimal(decimal) => {
+                out.push(DECIMAL);
+                encode_str(decimal.lexeme(), out);
+            }
+            Token::Keyword(keyword) => {
+                out.push(KEYWORD);
+                encode_str(keyword.as_str(), out);
+            }
+            Token::Operator(op) => {
+                out.push(OPERATOR);
+                encode_str(op.as_str(), out);
+            }
+            Token::StringLiteral(value) => {
+                out.push(STRING_LITERAL);
+                encode_str(value, out);
+            }
+            Token::Punctuation(punctuation) => {
+                out.push(PUNCTUATION);
+                encode_str(punctuation.as_str(), out);
+            }
+            Token::InterpolatedString(parts) => {
+                out.push(INTERPOLATED_STRING);
+                out.extend((parts.len() as u32).to_le_bytes());
+                for part in parts {
+                    match part {
+                        StringPart::Literal(text) => {
+                            out.push(LITERAL_PART);
+                            encode_str(text, out);
+                        }
+                        StringPart::Tokens(tokens) => {
+                            out.push(TOKENS_PART);
+                            encode_tokens(tokens, out);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn encode_str(text: &str, out: &mut Vec<u8>) {
+    out.extend((text.len() as u32).to_le_bytes());
+    out.extend(text.as_bytes());
+}
+
+/// Reads values from the front of a byte slice, returning `None` once the data runs out or is
+/// invalid
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if len > self.0.len() {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.array().map(u64::from_le_bytes)
+    }
+
+    fn str(&mut self) -> Option<&'a str> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?).ok()
+    }
+
+    /// Reads a list of tokens nested `depth` interpolations deep
+    ///
+    /// Lists nested deeper than the lexer allows are rejected, so a damaged file cannot make the
+    /// recursion overflow the stack.
+    fn tokens(&mut self, depth: usize) -> Option<Vec<Token>> {
+        if depth > MAX_INTERPOLATION_DEPTH {
+            return None;
+        }
+        let count = self.u32()? as usize;
+        // Every token takes at least one byte, which bounds the allocation for a damaged count
+        let mut tokens = Vec::with_capacity(count.min(self.0.len()));
+
+        for _ in 0..count {
+            let token = match self.u8()? {
+                INTEGER => Token::Integer(i64::from_le_bytes(self.array()?)),
+                WHITESPACE => Token::Whitespace,
+                IDENTIFIER => Token::Identifier(self.str()?.to_string()),
+                DECIMAL => Token::Decimal(DecimalLit::new(self.str()?)?),
+                KEYWORD => Token::Keyword(parse_keyword(self.str()?)?),
+                OPERATOR => Token::Operator(parse_operator(self.str()?)?),
+                STRING_LITERAL => Token::StringLiteral(self.str()?.to_string()),
+                PUNCTUATION => Token::Punctuation(parse_punctuation(self.str()?)?),
+                INTERPOLATED_STRING => {
+                    let count = self.u32()? as usize;
+                    let mut parts = Vec::with_capacity(count.min(self.0.len()));
+                    for _ in 0..count {
+                        parts.push(match self.u8()? {
+                            LITERAL_PART => StringPart::Literal(self.str()?.to_string()),
+                            TOKENS_PART => StringPart::Tokens(self.tokens(depth + 1)?),
+                            _ => return None,
+                        });
+                    }
+                    Token::InterpolatedString(parts)
+                }
+                _ => return None,
+            };
+            tokens.push(token);
+        }
+
+        Some(tokens)
+    }
+}
@@ -0,0 +1,108 @@
+//! # Checking
+//!
+//! Validates files without printing their tokens, for CI jobs that only need to know whether the
+//! input is clean. Every problem in a file is collected as a `Diagnostic`: each part of the input
+//! that cannot be lexed (as found by `lex_lossy`, so one error does not hide the next) and each
+//! identifier that looks like a misspelled keyword.
+
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::{LexError, LexItem, decode_source, lex_lossy, suggest};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Diagnostics found in a single file
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    pub path: PathBuf,
+    pub source: String,
+    /// Errors and warnings, ordered by where they start in the source
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CheckReport {
+    pub fn error_count(&self) -> usize {
+        self.count(Severity::Error)
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.count(Severity::Warning)
+    }
+
+    /// Returns `true` if the file has no errors, though it may have warnings
+    pub fn is_ok(&self) -> bool {
+        self.error_count() == 0
+    }
+
+    fn count(&self, severity: Severity) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|diag| diag.severity == severity)
+            .count()
+    }
+}
+
+/// Reads and checks a single file
+///
+/// A file that is not valid UTF-8 is reported with a single `InvalidUtf8` error, and its
+/// `source` is the text before the invalid byte.
+pub fn check(path: &Path) -> io::Result<CheckReport> {
+    let bytes = fs::read(path)?;
+    let (source, diagnostics) = match decode_source(&bytes) {
+        Ok(source) => (source.to_string(), check_source(source)),
+        Err(error) => (
+            String::from_utf8_lossy(&bytes[..error.span.start]).into_owned(),
+            vec![Diagnostic::from(error)],
+        ),
+    };
+
+    Ok(CheckReport {
+        path: path.to_path_buf(),
+        source,
+        diagnostics,
+    })
+}
+
+/// Returns the diagnostics for a source, ordered by where they start
+pub fn check_source(source: &str) -> Vec<Diagnostic> {
+    let mut tokens = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for item in lex_lossy(source) {
+        match item {
+            LexItem::Token { token, span } => tokens.push((token, span)),
+            LexItem::Error { span, reason } => {
+                diagnostics.push(Diagnostic::from(LexError { kind: reason, span }))
+            }
+        }
+    }
+    diagnostics.extend(
+        suggest::suggest_keywords(&tokens)
+            .iter()
+            .map(suggest::Suggestion::to_diagnostic),
+    );
+    diagnostics.sort_by_key(|diag| diag.span.start);
+
+    diagnostics
+}
+
+/// Formats the totals of a check as a single line, e.g. `3 files checked, 1 error, 2 warnings`
+///
+/// `unreadable` files count as checked, with one error each.
+pub fn summary(reports: &[CheckReport], unreadable: usize) -> String {
+    let errors: usize = reports.iter().map(CheckReport::error_count).sum::<usize>() + unreadable;
+    let warnings: usize = reports.iter().map(CheckReport::warning_count).sum();
+
+    format!(
+        "{}, {}, {}",
+        plural(reports.len() + unreadable, "file") + " checked",
+        plural(errors, "error"),
+        plural(warnings, "warning")
+    )
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {}", noun),
+        _ => format!("{} {}s", count, noun),
+    }
+}
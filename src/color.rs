@@ -0,0 +1,93 @@
+//! # Terminal Colors
+//!
+//! Styles output with ANSI escape sequences, coloring each token by its kind:
+//!
+//! | Token                | Color   |
+//! |----------------------|---------|
+//! | `Keyword`            | blue    |
+//! | `Integer`, `Decimal` | yellow  |
+//! | `Operator`           | magenta |
+//! | `StringLiteral`      | green   |
+//! | `InterpolatedString` | green   |
+//!
+//! Other tokens keep the default color. Errors are red and warnings yellow.
+//!
+//! Whether to color is decided by a `ColorChoice`: `Auto` colors only when writing to a terminal
+//! and the `NO_COLOR` environment variable is unset or empty (see <https://no-color.org>).
+
+use crate::TokenKind;
+use crate::diagnostic::Severity;
+use std::io::IsTerminal;
+
+/// When output is colored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Parses `auto`, `always` or `never`
+    pub fn parse(text: &str) -> Option<ColorChoice> {
+        match text {
+            "auto" => Some(ColorChoice::Auto),
+            "always" => Some(ColorChoice::Always),
+            "never" => Some(ColorChoice::Never),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if output to a stream should be colored, given whether it is a terminal and
+    /// whether `NO_COLOR` is set
+    pub fn enabled(self, is_terminal: bool, no_color: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal && !no_color,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+
+    /// Returns `true` if output to `stream` should be colored, reading `NO_COLOR` from the
+    /// environment
+    pub fn enabled_for(self, stream: &impl IsTerminal) -> bool {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        self.enabled(stream.is_terminal(), no_color)
+    }
+}
+
+/// Returns the ANSI style of a kind of token, or `None` if it keeps the default color
+pub fn token_style(kind: TokenKind) -> Option<&'static str> {
+    match kind {
+        TokenKind::Keyword => Some(BLUE),
+        TokenKind::Integer | TokenKind::Decimal => Some(YELLOW),
+        TokenKind::Operator => Some(MAGENTA),
+        TokenKind::StringLiteral | TokenKind::InterpolatedString => Some(GREEN),
+        TokenKind::Identifier | TokenKind::Punctuation | TokenKind::Whitespace => None,
+    }
+}
+
+/// Returns the ANSI style of a diagnostic of the given severity
+pub fn severity_style(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+    }
+}
+
+/// Wraps text in an ANSI style, or returns it unchanged if `style` is `None`
+pub fn paint(text: &str, style: Option<&str>) -> String {
+    match style {
+        Some(style) => format!("\x1b[{}m{}{}", style, text, RESET),
+        None => text.to_string(),
+    }
+}
+
+pub const RED: &str = "31";
+pub const GREEN: &str = "32";
+pub const YELLOW: &str = "33";
+pub const BLUE: &str = "34";
+pub const MAGENTA: &str = "35";
+const RESET: &str = "\x1b[0m";
@@ -0,0 +1,235 @@
+//! # Diagnostics
+//!
+//! Renders errors as an excerpt of the offending source line with a `^^^` underline below the
+//! part of the line covered by the error's span:
+//!
+//! ```text
+//! error: Unrecognized token starting at position 6: '$'
+//!  --> 1:7
+//!   |
+//! 1 | print $x + 3
+//!   |       ^
+//! ```
+//!
+//! Columns count characters (Unicode scalar values), so `á` takes one column however many bytes
+//! it is. Tabs in the excerpt are expanded to the next multiple of `RenderOptions::tab_width` so the
+//! carets line up with the characters above them.
+//!
+//! `to_json` formats a diagnostic as a single line of JSON instead, for tools that consume them.
+
+use crate::line_index::LineIndex;
+use crate::{LexError, Span, color, json};
+use std::fmt;
+
+/// Settings for `render_with_options`
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Number of columns a tab advances to when a source line is displayed
+    pub tab_width: usize,
+    /// Whether the severity and underline are colored (see `color`)
+    pub color: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            tab_width: 4,
+            color: false,
+        }
+    }
+}
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A message about a region of the source, with optional extra notes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+    /// Stable identifier of the kind of problem (e.g. `L0001`)
+    pub code: Option<&'static str>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+            code: None,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            ..Diagnostic::error(message, span)
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Appends a note shown below the source excerpt
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+impl From<LexError> for Diagnostic {
+    fn from(error: LexError) -> Self {
+        Diagnostic::error(error.to_string(), error.span).with_code(error.kind.code())
+    }
+}
+
+/// Renders a diagnostic as a header, the source line containing the start of its span, a caret
+/// underline, and its notes
+///
+/// Spans that continue past the end of their first line are underlined up to the end of that line.
+pub fn render(diag: &Diagnostic, source: &str) -> String {
+    render_with_options(diag, source, &RenderOptions::default())
+}
+
+/// Renders a diagnostic like `render`, with the given settings
+pub fn render_with_options(diag: &Diagnostic, source: &str, options: &RenderOptions) -> String {
+    render_excerpt(diag, None, source, options)
+}
+
+/// Renders a diagnostic like `render_with_options`, naming `file` before the line and column
+pub fn render_in_file(
+    diag: &Diagnostic,
+    file: &str,
+    source: &str,
+    options: &RenderOptions,
+) -> String {
+    render_excerpt(diag, Some(file), source, options)
+}
+
+fn render_excerpt(
+    diag: &Diagnostic,
+    file: Option<&str>,
+    source: &str,
+    options: &RenderOptions,
+) -> String {
+    let tab_width = options.tab_width.max(1);
+    let start = diag.span.start.min(source.len());
+    let index = LineIndex::new(source);
+    let (line_number, column) = index.line_col(start);
+    let line_start = index.line_range(line_number).map_or(0, |range| range.start);
+    let line = index.line_text(line_number);
+
+    let before = &line[..(start - line_start).min(line.len())];
+    let underlined = &line[before.len()..(diag.span.end.max(start) - line_start).min(line.len())];
+    let caret_offset = display_width(before, 0, tab_width);
+    let caret_count = (display_width(underlined, caret_offset, tab_width) - caret_offset).max(1);
+
+    let gutter = " ".repeat(line_number.to_string().len());
+    let style = options.color.then(|| color::severity_style(diag.severity));
+    let severity = color::paint(&diag.severity.to_string(), style);
+    let mut out = format!("{}: {}\n", severity, diag.message);
+    match file {
+        Some(file) => out += &format!("{}--> {}:{}:{}\n", gutter, file, line_number, column),
+        None => out += &format!("{}--> {}:{}\n", gutter, line_number, column),
+    }
+    out += &format!("{} |\n", gutter);
+    out += &format!("{} | {}\n", line_number, expand_tabs(line, tab_width));
+    out += &format!(
+        "{} | {}{}\n",
+        gutter,
+        " ".repeat(caret_offset),
+        color::paint(&"^".repeat(caret_count), style)
+    );
+    for note in &diag.notes {
+        out += &format!("{} = note: {}\n", gutter, note);
+    }
+
+    out
+}
+
+/// Formats a diagnostic about `file` as a JSON object on a single line
+///
+/// The object has the fields `severity`, `code` (`null` if the diagnostic has none), `message`,
+/// `file`, and the 1-based `line`, `column`, `end_line` and `end_column` of its span. Notes are
+/// appended to the message on lines of their own.
+pub fn to_json(diag: &Diagnostic, file: &str, source: &str) -> String {
+    let index = LineIndex::new(source);
+    let (line, column) = index.line_col(diag.span.start);
+    let (end_line, end_column) = index.line_col(diag.span.end);
+    let mut message = diag.message.clone();
+    for note in &diag.notes {
+        message += &format!("\nnote: {}", note);
+    }
+
+    format!(
+        "{{\"severity\": {}, \"code\": {}, \"message\": {}, \"file\": {}, \"line\": {}, \
+         \"column\": {}, \"end_line\": {}, \"end_column\": {}}}",
+        json::string(&diag.severity.to_string()),
+        diag.code.map_or(String::from("null"), json::string),
+        json::string(&message),
+        json::string(file),
+        line,
+        column,
+        end_line,
+        end_column
+    )
+}
+
+/// Returns the 1-based line and column (counted in characters) of a byte offset in the source
+///
+/// Lines end at `\n`, so the `\r` of a `\r\n` ending is the last character of its line and a
+/// lone `\r` counts as one column.
+///
+/// Builds a `LineIndex` for a single lookup; build one directly to convert many offsets.
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let (line, column) = LineIndex::new(source).line_col(offset);
+    (line as usize, column as usize)
+}
+
+/// Returns the display column reached after printing `text` starting at column `start`
+fn display_width(text: &str, start: usize, tab_width: usize) -> usize {
+    text.chars().fold(start, |column, c| match c {
+        '\t' => column + tab_width - column % tab_width,
+        _ => column + 1,
+    })
+}
+
+/// Replaces each tab with the spaces needed to reach the next tab stop, and each other whitespace
+/// control character (such as a lone `\r`) with a space so it cannot disturb the terminal
+fn expand_tabs(line: &str, tab_width: usize) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+
+    for c in line.chars() {
+        let next_column = display_width(c.encode_utf8(&mut [0; 4]), column, tab_width);
+        if c == '\t' {
+            expanded.push_str(&" ".repeat(next_column - column));
+        } else if c.is_ascii_whitespace() || c == '\x0b' {
+            expanded.push(' ');
+        } else {
+            expanded.push(c);
+        }
+        column = next_column;
+    }
+
+    expanded
+}
@@ -0,0 +1,305 @@
+//! # Token Diff
+//!
+//! Compares two sources token by token, ignoring whitespace and formatting, using a longest
+//! common subsequence over the two token sequences, found in linear memory so large files can be
+//! compared. Consecutive differences are grouped into hunks, each with the span it covers in both
+//! files.
+//!
+//! With `DiffOptions::ignore_identifier_names`, identifiers are compared by the order in which
+//! each distinct name first appears rather than by the name itself, so a copy with every
+//! variable renamed still matches the original. This includes the identifiers inside the `${...}`
+//! of interpolated strings.
+
+use crate::line_index::LineIndex;
+use crate::{LexError, LexerOptions, Span, StringPart, Token, extract_tokens_with_options};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Settings for a token diff
+#[derive(Debug, Clone, Default)]
+pub struct DiffOptions {
+    /// Compare identifiers by position of first appearance instead of by name
+    pub ignore_identifier_names: bool,
+}
+
+/// What happened to a run of tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HunkKind {
+    /// Tokens only present in the second source
+    Inserted,
+    /// Tokens only present in the first source
+    Deleted,
+    /// Tokens of the first source replaced by different ones in the second
+    Changed,
+}
+
+/// A run of consecutive differing tokens
+///
+/// A side with no tokens in the hunk has an empty span placed where the tokens would be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub kind: HunkKind,
+    /// Range covered in the first source
+    pub a: Span,
+    /// Range covered in the second source
+    pub b: Span,
+}
+
+/// Differences between two token sequences
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenDiff {
+    pub hunks: Vec<Hunk>,
+}
+
+impl TokenDiff {
+    /// Returns `true` if both sources have the same tokens
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+/// Represents a source that could not be split into tokens
+#[derive(Debug, PartialEq)]
+pub enum DiffError {
+    /// The first source has a lex error
+    First(LexError),
+    /// The second source has a lex error
+    Second(LexError),
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiffError::First(error) => write!(f, "In the first source: {}", error),
+            DiffError::Second(error) => write!(f, "In the second source: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for DiffError {}
+
+/// Compares the tokens of two sources
+pub fn diff_tokens(a: &str, b: &str) -> Result<TokenDiff, DiffError> {
+    diff_tokens_with_options(a, b, &DiffOptions::default())
+}
+
+/// Compares the tokens of two sources using the given options
+pub fn diff_tokens_with_options(
+    a: &str,
+    b: &str,
+    options: &DiffOptions,
+) -> Result<TokenDiff, DiffError> {
+    let lexer_options = LexerOptions {
+        emit_whitespace: false,
+    };
+    let a_tokens = extract_tokens_with_options(a, &lexer_options).map_err(DiffError::First)?;
+    let b_tokens = extract_tokens_with_options(b, &lexer_options).map_err(DiffError::Second)?;
+
+    let a_keys = comparison_keys(&a_tokens, options);
+    let b_keys = comparison_keys(&b_tokens, options);
+
+    let mut hunks = Vec::new();
+    let mut pending: Option<(usize, usize, usize, usize)> = None;
+    let mut flush = |pending: &mut Option<(usize, usize, usize, usize)>| {
+        if let Some((a_start, a_end, b_start, b_end)) = pending.take() {
+            let kind = match (a_end > a_start, b_end > b_start) {
+                (true, true) => HunkKind::Changed,
+                (true, false) => HunkKind::Deleted,
+                _ => HunkKind::Inserted,
+            };
+            hunks.push(Hunk {
+                kind,
+                a: token_range_span(&a_tokens, a_start, a_end, a.len()),
+                b: token_range_span(&b_tokens, b_start, b_end, b.len()),
+            });
+        }
+    };
+
+    for step in edit_script(&a_keys, &b_keys) {
+        match step {
+            Step::Equal => flush(&mut pending),
+            Step::Delete(i, j) => {
+                let hunk = pending.get_or_insert((i, i, j, j));
+                hunk.1 = i + 1;
+            }
+            Step::Insert(i, j) => {
+                let hunk = pending.get_or_insert((i, i, j, j));
+                hunk.3 = j + 1;
+            }
+        }
+    }
+    flush(&mut pending);
+
+    Ok(TokenDiff { hunks })
+}
+
+/// Returns the values compared for each token, renaming identifiers if requested
+fn comparison_keys(tokens: &[(Token, Span)], options: &DiffOptions) -> Vec<Token> {
+    if !options.ignore_identifier_names {
+        return tokens.iter().map(|(token, _)| token.clone()).collect();
+    }
+
+    let mut names: HashMap<String, usize> = HashMap::new();
+    tokens
+        .iter()
+        .map(|(token, _)| rename_identifiers(token, &mut names))
+        .collect()
+}
+
+/// Replaces each identifier in a token, including those in interpolations, by the position at
+/// which its name first appeared
+fn rename_identifiers(token: &Token, names: &mut HashMap<String, usize>) -> Token {
+    match token {
+        Token::Identifier(name) => {
+            let next = names.len();
+            let position = *names.entry(name.clone()).or_insert(next);
+            Token::Identifier(format!("#{}", position))
+        }
+        Token::InterpolatedString(parts) => Token::InterpolatedString(
+            parts
+                .iter()
+                .map(|part| match part {
+                    StringPart::Literal(_) => part.clone(),
+                    StringPart::Tokens(tokens) => StringPart::Tokens(
+                        tokens
+                            .iter()
+                            .map(|token| rename_identifiers(token, names))
+                            .collect(),
+                    ),
+                })
+                .collect(),
+        ),
+        _ => token.clone(),
+    }
+}
+
+/// Returns the span covering tokens `start..end`, or an empty span before token `start` if the
+/// range is empty
+fn token_range_span(tokens: &[(Token, Span)], start: usize, end: usize, len: usize) -> Span {
+    let position = |index: usize| tokens.get(index).map_or(len, |(_, span)| span.start);
+
+    if end > start {
+        Span::new(tokens[start].1.start, tokens[end - 1].1.end)
+    } else {
+        Span::new(position(start), position(start))
+    }
+}
+
+/// A step of the edit script turning the first sequence into the second, with the indices of the
+/// tokens a deletion or insertion happens at
+enum Step {
+    Equal,
+    Delete(usize, usize),
+    Insert(usize, usize),
+}
+
+/// Computes an edit script from a longest common subsequence
+///
+/// The common prefix and suffix are matched directly, and the rest with Hirschberg's algorithm,
+/// which takes time proportional to the product of the lengths of what is left but only linear
+/// memory.
+fn edit_script(a: &[Token], b: &[Token]) -> Vec<Step> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let mut steps: Vec<Step> = (0..prefix).map(|_| Step::Equal).collect();
+    align(
+        &a[prefix..a.len() - suffix],
+        &b[prefix..b.len() - suffix],
+        (prefix, prefix),
+        &mut steps,
+    );
+    steps.extend((0..suffix).map(|_| Step::Equal));
+
+    steps
+}
+
+/// Appends the steps turning `a` into `b`, where `offset` is the index of their first tokens in
+/// the whole sequences
+///
+/// `a` is split in half and `b` at the point where the longest common subsequences of the two
+/// halves add up to the longest overall, then each pair of halves is aligned on its own.
+fn align(a: &[Token], b: &[Token], (i, j): (usize, usize), steps: &mut Vec<Step>) {
+    match a {
+        [] => steps.extend((0..b.len()).map(|k| Step::Insert(i, j + k))),
+        [token] => match b.iter().position(|other| other == token) {
+            Some(k) => {
+                steps.extend((0..k).map(|k| Step::Insert(i, j + k)));
+                steps.push(Step::Equal);
+                steps.extend((k + 1..b.len()).map(|k| Step::Insert(i + 1, j + k)));
+            }
+            None => {
+                steps.push(Step::Delete(i, j));
+                steps.extend((0..b.len()).map(|k| Step::Insert(i + 1, j + k)));
+            }
+        },
+        _ if b.is_empty() => steps.extend((0..a.len()).map(|k| Step::Delete(i + k, j))),
+        _ => {
+            let middle = a.len() / 2;
+            let forward = lcs_lengths(a[..middle].iter(), b.iter());
+            let backward = lcs_lengths(a[middle..].iter().rev(), b.iter().rev());
+            let split = (0..=b.len())
+                .max_by_key(|&k| (forward[k] + backward[b.len() - k], std::cmp::Reverse(k)))
+                .unwrap_or(0);
+
+            align(&a[..middle], &b[..split], (i, j), steps);
+            align(&a[middle..], &b[split..], (i + middle, j + split), steps);
+        }
+    }
+}
+
+/// Returns the length of the longest common subsequence of `a` and each prefix of `b`, indexed by
+/// the length of the prefix
+fn lcs_lengths<'a>(
+    a: impl Iterator<Item = &'a Token>,
+    b: impl Iterator<Item = &'a Token> + Clone,
+) -> Vec<usize> {
+    let mut previous = vec![0; b.clone().count() + 1];
+    let mut current = previous.clone();
+
+    for x in a {
+        for (k, y) in b.clone().enumerate() {
+            current[k + 1] = if x == y {
+                previous[k] + 1
+            } else {
+                previous[k + 1].max(current[k])
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous
+}
+
+/// Formats the diff as a unified-style report with the text of the differing tokens
+pub fn format_diff(diff: &TokenDiff, a_name: &str, a: &str, b_name: &str, b: &str) -> String {
+    let mut out = format!("--- {}\n+++ {}\n", a_name, b_name);
+    let (a_index, b_index) = (LineIndex::new(a), LineIndex::new(b));
+
+    for hunk in &diff.hunks {
+        let (a_line, a_column) = a_index.line_col(hunk.a.start);
+        let (b_line, b_column) = b_index.line_col(hunk.b.start);
+        out += &format!(
+            "@@ -{}:{} +{}:{} @@ {:?}\n",
+            a_line, a_column, b_line, b_column, hunk.kind
+        );
+        if hunk.kind != HunkKind::Inserted {
+            out += &format!("- {}\n", single_line(&a[hunk.a.start..hunk.a.end]));
+        }
+        if hunk.kind != HunkKind::Deleted {
+            out += &format!("+ {}\n", single_line(&b[hunk.b.start..hunk.b.end]));
+        }
+    }
+
+    out
+}
+
+/// Collapses every run of whitespace into a single space
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,173 @@
+//! # Documentation Comments
+//!
+//! Extracts the documentation of functions from `///` and `/** */` comments. A run of doc
+//! comments documents the `fn` that comes right after it:
+//!
+//! ```text
+//! /// Adds one to `x`
+//! /// and returns it
+//! fn increment x
+//! ```
+//!
+//! A blank line, an ordinary comment or any token other than `fn` ends the run, leaving its
+//! comments attached to nothing.
+
+use crate::{Keyword, LexError, Span, Token, comment_len, extract_tokens_with_spans, json};
+
+/// What kind of item a doc comment documents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Function,
+}
+
+impl ItemKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Function => "function",
+        }
+    }
+}
+
+/// Documentation attached to an item
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocEntry {
+    pub item_name: String,
+    pub kind: ItemKind,
+    /// Text of the comments without their delimiters, one line per line of documentation
+    pub text: String,
+    /// Range covered by the doc comments
+    pub span: Span,
+}
+
+/// Lexes the source and returns the documentation of each documented function
+pub fn extract_docs(source: &str) -> Result<Vec<DocEntry>, LexError> {
+    let tokens = extract_tokens_with_spans(source)?;
+    Ok(docs_from_tokens(source, &tokens))
+}
+
+/// Returns the documentation of each documented function, given the tokens of the source
+/// including whitespace
+pub fn docs_from_tokens(source: &str, tokens: &[(Token, Span)]) -> Vec<DocEntry> {
+    let mut entries = Vec::new();
+    // Lines and span of the doc comments seen since the last token
+    let mut pending: Option<(Vec<String>, Span)> = None;
+
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        match token {
+            Token::Whitespace => {
+                // Skip the byte order mark and `#!` line, which may contain `//`
+                let start = match span.start {
+                    0 => crate::prefix_len(source).min(span.end),
+                    start => start,
+                };
+                collect_doc_comments(&source[start..span.end], start, &mut pending);
+            }
+            Token::Keyword(Keyword::Fn) => {
+                let name = tokens[index + 1..]
+                    .iter()
+                    .find(|(token, _)| *token != Token::Whitespace);
+                if let (Some((lines, doc_span)), Some((Token::Identifier(name), _))) =
+                    (pending.take(), name)
+                {
+                    entries.push(DocEntry {
+                        item_name: name.clone(),
+                        kind: ItemKind::Function,
+                        text: lines.join("\n"),
+                        span: doc_span,
+                    });
+                }
+            }
+            _ => pending = None,
+        }
+    }
+
+    entries
+}
+
+/// Adds the doc comments of a run of whitespace and comments starting at `base` to `pending`,
+/// dropping it at a blank line or an ordinary comment
+fn collect_doc_comments(text: &str, base: usize, pending: &mut Option<(Vec<String>, Span)>) {
+    let mut offset = 0;
+    // Line breaks since the last comment
+    let mut line_breaks = 0;
+
+    while let Some(c) = text[offset..].chars().next() {
+        let Some(Ok(len)) = comment_len(&text[offset..]) else {
+            if c == '\n' {
+                line_breaks += 1;
+                if line_breaks == 2 {
+                    *pending = None;
+                }
+            }
+            offset += c.len_utf8();
+            continue;
+        };
+
+        let span = Span::new(base + offset, base + offset + len);
+        match (doc_lines(&text[offset..offset + len]), pending.as_mut()) {
+            (Some(lines), Some((doc, doc_span))) => {
+                doc.extend(lines);
+                doc_span.end = span.end;
+            }
+            (Some(lines), None) => *pending = Some((lines, span)),
+            (None, _) => *pending = None,
+        }
+        line_breaks = 0;
+        offset += len;
+    }
+}
+
+/// Returns the lines of text of a doc comment, or `None` for an ordinary comment
+///
+/// `////` and `/***` start ordinary comments. Block comments lose the indentation and leading `*`
+/// of each line, and their blank first and last lines.
+fn doc_lines(comment: &str) -> Option<Vec<String>> {
+    if let Some(text) = comment.strip_prefix("///") {
+        if text.starts_with('/') {
+            return None;
+        }
+        let text = text.strip_prefix(' ').unwrap_or(text);
+        return Some(vec![text.trim_end().to_string()]);
+    }
+
+    let body = comment.strip_prefix("/**")?.strip_suffix("*/")?;
+    if body.starts_with('*') {
+        return None;
+    }
+    let lines: Vec<String> = body
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*')
+                .map_or(line, str::trim_start)
+                .to_string()
+        })
+        .collect();
+    let first = lines.iter().position(|line| !line.is_empty())?;
+    let last = lines.iter().rposition(|line| !line.is_empty())?;
+
+    Some(lines[first..=last].to_vec())
+}
+
+/// Formats the entries as a JSON array with one object per line
+pub fn docs_to_json(entries: &[DocEntry]) -> String {
+    if entries.is_empty() {
+        return String::from("[]\n");
+    }
+
+    let objects: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "  {{\"name\": {}, \"kind\": {}, \"text\": {}, \"start\": {}, \"end\": {}}}",
+                json::string(&entry.item_name),
+                json::string(entry.kind.as_str()),
+                json::string(&entry.text),
+                entry.span.start,
+                entry.span.end
+            )
+        })
+        .collect();
+
+    format!("[\n{}\n]\n", objects.join(",\n"))
+}
@@ -0,0 +1,209 @@
+//! # Formatting
+//!
+//! Re-emits the tokens of a source with normalized spacing. The tokens themselves are copied
+//! exactly as written, so formatting never changes what a source lexes to:
+//!
+//! - Tokens on a line are separated by one space, except around punctuation: there is no space
+//!   after `[`, before `]` or before `,`, one space after `,`, and none before a `[` that indexes
+//!   (one that follows an identifier, a string or a `]`). Operators always keep their space, as
+//!   `!x` would not lex.
+//! - Line breaks are kept where they are, with runs of blank lines cut down to
+//!   `FormatStyle::max_blank_lines`. Trailing whitespace is removed and the output ends with a
+//!   single line break.
+//! - Blocks are marked by indentation, so each line is indented by `FormatStyle::indent_width`
+//!   spaces per level, where the levels are the distinct indentations of the enclosing lines. A
+//!   tab counts as `indent_width` spaces.
+//! - Comments are kept as written, on the line they were on.
+//!
+//! Formatting is idempotent: formatting the output again returns it unchanged.
+//!
+//! `minify` does the opposite, removing comments and every space and line break the lexer does
+//! not need. Only punctuation separates tokens by itself, so any other two tokens in a row keep a
+//! single space between them: `a - - b` stays as it is, while `xs[0] = [1, 2]` becomes
+//! `xs[0]=[1,2]`.
+
+use crate::{
+    LexError, Punctuation, Token, TokenKind, comment_len, extract_tokens_with_spans,
+    needs_separator, prefix_len,
+};
+
+/// Settings for `format_source`
+#[derive(Debug, Clone)]
+pub struct FormatStyle {
+    /// Number of spaces per indentation level
+    pub indent_width: usize,
+    /// Most blank lines kept in a row
+    pub max_blank_lines: usize,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        FormatStyle {
+            indent_width: 4,
+            max_blank_lines: 1,
+        }
+    }
+}
+
+/// Returns the source with its spacing normalized
+pub fn format_source(source: &str, style: &FormatStyle) -> Result<String, LexError> {
+    let tokens = extract_tokens_with_spans(source)?;
+    let prefix = prefix_len(source);
+    let mut formatter = Formatter {
+        style,
+        out: source[..prefix].to_string(),
+        previous: source[..prefix].contains("#!").then_some(Previous::Comment),
+        indents: vec![0],
+    };
+
+    let mut offset = prefix;
+    for (token, span) in tokens {
+        if token == Token::Whitespace {
+            continue;
+        }
+        let position = formatter.trivia(&source[offset..span.start]);
+        formatter.token(position, token, &source[span.start..span.end]);
+        offset = span.end;
+    }
+    formatter.trivia(&source[offset..]);
+
+    if formatter.previous.is_some() {
+        formatter.out.push('\n');
+    }
+    Ok(formatter.out)
+}
+
+/// Returns the source with its comments removed and its tokens as close together as they can be
+///
+/// A `#!` line at the start is kept, so scripts stay executable.
+pub fn minify(source: &str) -> Result<String, LexError> {
+    let tokens = extract_tokens_with_spans(source)?;
+    let prefix = &source[..prefix_len(source)];
+    let mut out = prefix.to_string();
+    if prefix.contains("#!") {
+        out.push('\n');
+    }
+
+    let mut previous: Option<TokenKind> = None;
+    for (token, span) in tokens {
+        if token == Token::Whitespace {
+            continue;
+        }
+        if previous.is_some_and(|previous| needs_separator(previous, token.kind())) {
+            out.push(' ');
+        }
+        out.push_str(&source[span.start..span.end]);
+        previous = Some(token.kind());
+    }
+
+    Ok(out)
+}
+
+/// What was last written to the output
+#[derive(Debug, Clone, PartialEq)]
+enum Previous {
+    Token(Token),
+    Comment,
+}
+
+struct Formatter<'a> {
+    style: &'a FormatStyle,
+    out: String,
+    /// `None` until something other than the byte order mark has been written
+    previous: Option<Previous>,
+    /// Indentation widths of the enclosing blocks in the source, starting with 0
+    indents: Vec<usize>,
+}
+
+impl Formatter<'_> {
+    /// Writes the comments in the whitespace before a token, keeping their line breaks
+    ///
+    /// Returns the number of line breaks after the last comment and the indentation of the line
+    /// the whitespace ends on, for placing the token.
+    fn trivia(&mut self, text: &str) -> (usize, usize) {
+        let mut line_breaks = 0;
+        let mut width = 0;
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            if let Some(Ok(len)) = comment_len(rest) {
+                self.start(line_breaks, width, None);
+                self.out.push_str(rest[..len].trim_end());
+                self.previous = Some(Previous::Comment);
+                line_breaks = 0;
+                rest = &rest[len..];
+                continue;
+            }
+
+            match c {
+                '\n' => {
+                    line_breaks += 1;
+                    width = 0;
+                }
+                '\t' => width += self.style.indent_width,
+                '\r' => {}
+                _ => width += 1,
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+
+        (line_breaks, width)
+    }
+
+    /// Writes a token as it was written in the source
+    fn token(&mut self, (line_breaks, width): (usize, usize), token: Token, text: &str) {
+        self.start(line_breaks, width, Some(&token));
+        self.out.push_str(text);
+        self.previous = Some(Previous::Token(token));
+    }
+
+    /// Writes what goes between the previous token or comment and the next (`None` for a comment)
+    fn start(&mut self, line_breaks: usize, width: usize, next: Option<&Token>) {
+        let Some(previous) = &self.previous else {
+            self.indent(width);
+            return;
+        };
+
+        if line_breaks == 0 {
+            self.out.push_str(separator(previous, next));
+            return;
+        }
+        let line_breaks = line_breaks.min(self.style.max_blank_lines + 1);
+        self.out.extend(std::iter::repeat_n('\n', line_breaks));
+        self.indent(width);
+    }
+
+    /// Indents a line by the level of a source indentation of `width`
+    fn indent(&mut self, width: usize) {
+        while self.indents.len() > 1 && self.indents.last() > Some(&width) {
+            self.indents.pop();
+        }
+        if self.indents.last() < Some(&width) {
+            self.indents.push(width);
+        }
+
+        let level = self.indents.len() - 1;
+        self.out
+            .extend(std::iter::repeat_n(' ', level * self.style.indent_width));
+    }
+}
+
+/// Returns the spacing between two tokens on the same line (`None` for a comment)
+fn separator(previous: &Previous, next: Option<&Token>) -> &'static str {
+    let Previous::Token(previous) = previous else {
+        return " ";
+    };
+
+    match (previous, next) {
+        (_, Some(Token::Punctuation(Punctuation::Comma | Punctuation::RightBracket))) => "",
+        (Token::Punctuation(Punctuation::LeftBracket), _) => "",
+        (
+            Token::Identifier(_)
+            | Token::StringLiteral(_)
+            | Token::InterpolatedString(_)
+            | Token::Punctuation(Punctuation::RightBracket),
+            Some(Token::Punctuation(Punctuation::LeftBracket)),
+        ) => "",
+        _ => " ",
+    }
+}
@@ -0,0 +1,95 @@
+//! # HTML Highlighting
+//!
+//! Renders source code as HTML with every token wrapped in a `<span>` whose class depends on the
+//! kind of token, so it can be styled with CSS:
+//!
+//! | Token                    | Class     |
+//! |--------------------------|-----------|
+//! | `Keyword`                | `kw`      |
+//! | `Identifier`             | `ident`   |
+//! | `Integer`                | `num`     |
+//! | `Decimal`                | `num`     |
+//! | `Operator`               | `op`      |
+//! | `StringLiteral`          | `string`  |
+//! | `InterpolatedString`     | `string`  |
+//! | `Punctuation`            | `punct`   |
+//! | Comments (`//`, `/* */`) | `comment` |
+//!
+//! Comments are part of the whitespace tokens, so they are found by scanning the whitespace. The
+//! output is rebuilt from the original text using the token spans, so whitespace is preserved
+//! exactly and any text not covered by a token is kept unstyled instead of being dropped.
+
+use crate::{LexError, Span, Token, TokenKind, comment_spans, extract_tokens_with_spans};
+
+/// Returns the CSS class used for a kind of token, or `None` if it is left unstyled
+pub fn css_class(kind: TokenKind) -> Option<&'static str> {
+    match kind {
+        TokenKind::Keyword => Some("kw"),
+        TokenKind::Identifier => Some("ident"),
+        TokenKind::Integer | TokenKind::Decimal => Some("num"),
+        TokenKind::Operator => Some("op"),
+        TokenKind::StringLiteral | TokenKind::InterpolatedString => Some("string"),
+        TokenKind::Punctuation => Some("punct"),
+        TokenKind::Whitespace => None,
+    }
+}
+
+/// Lexes the source and returns it as highlighted HTML, without any surrounding document
+pub fn highlight_html(source: &str) -> Result<String, LexError> {
+    let tokens = extract_tokens_with_spans(source)?;
+    let mut html = String::with_capacity(source.len() * 2);
+    let mut offset = 0;
+
+    for (token, span) in tokens {
+        html.push_str(&escape_html(&source[offset..span.start]));
+
+        if token == Token::Whitespace {
+            html.push_str(&highlight_comments(source, span));
+            offset = span.end;
+            continue;
+        }
+
+        let text = escape_html(&source[span.start..span.end]);
+        match css_class(token.kind()) {
+            Some(class) => html.push_str(&format!("<span class=\"{}\">{}</span>", class, text)),
+            None => html.push_str(&text),
+        }
+        offset = span.end;
+    }
+    html.push_str(&escape_html(&source[offset..]));
+
+    Ok(html)
+}
+
+/// Returns a run of whitespace and comments as HTML, with each comment in a `comment` span
+fn highlight_comments(source: &str, span: Span) -> String {
+    let mut html = String::new();
+    let mut offset = span.start;
+
+    for comment in comment_spans(source, span) {
+        html.push_str(&escape_html(&source[offset..comment.start]));
+        html.push_str(&format!(
+            "<span class=\"comment\">{}</span>",
+            escape_html(&source[comment.start..comment.end])
+        ));
+        offset = comment.end;
+    }
+    html.push_str(&escape_html(&source[offset..span.end]));
+
+    html
+}
+
+/// Escapes the characters that have a special meaning in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
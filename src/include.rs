@@ -0,0 +1,289 @@
+//! # Includes
+//!
+//! Splices the tokens of other files into a program. A line starting with `include` followed by a
+//! string literal (e.g. `include "lib.lang"`) is replaced by the tokens of the named file, whose
+//! path is relative to the directory of the including file. Included files may include others,
+//! and a file may be included more than once (for instance by two files that are both included),
+//! but a file that ends up including itself is an error.
+//!
+//! `include` is an ordinary identifier anywhere else, so this is a step between lexing and parsing
+//! that programs without includes never notice.
+//!
+//! Every token is paired with a `FileSpan` naming the file it came from. The files are recorded in
+//! a `SourceMap`, so diagnostics can show the right file and line.
+
+use crate::diagnostic::Diagnostic;
+use crate::{LexError, Span, Token, decode_source, extract_tokens_with_spans};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+
+/// Diagnostic code of the error about a file that cannot be included
+pub const MISSING_INCLUDE: &str = "I0001";
+
+/// Diagnostic code of the error about a file that includes itself
+pub const INCLUDE_CYCLE: &str = "I0002";
+
+/// Identifies a file in a `SourceMap`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId(u32);
+
+/// A span of the file it belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSpan {
+    pub file: FileId,
+    pub span: Span,
+}
+
+/// A file read while lexing a program
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    /// Path the file was read from, as given or joined onto the directory of its includer
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// The files of a program, each read once even if it is included several times
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    /// Records a file and returns its id
+    pub fn add(&mut self, path: impl Into<PathBuf>, source: impl Into<String>) -> FileId {
+        self.files.push(SourceFile {
+            path: path.into(),
+            source: source.into(),
+        });
+        FileId(self.files.len() as u32 - 1)
+    }
+
+    /// Returns a file recorded in this map
+    ///
+    /// # Panics
+    /// Panics if the id belongs to another map.
+    pub fn get(&self, file: FileId) -> &SourceFile {
+        &self.files[file.0 as usize]
+    }
+
+    pub fn path(&self, file: FileId) -> &Path {
+        &self.get(file).path
+    }
+
+    pub fn source(&self, file: FileId) -> &str {
+        &self.get(file).source
+    }
+
+    /// Returns the number of files recorded
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Represents the reasons why the files of a program cannot be lexed
+#[derive(Debug)]
+pub enum IncludeError {
+    /// A file could not be read; `include` is the `include` line naming it, or `None` for the
+    /// file the program starts from
+    Read {
+        path: PathBuf,
+        error: io::Error,
+        include: Option<FileSpan>,
+    },
+    /// The file named by the `include` line is already being included further up the chain
+    Cycle { path: PathBuf, include: FileSpan },
+    /// The contents of a file could not be split into tokens
+    Lex { file: FileId, error: LexError },
+}
+
+impl IncludeError {
+    /// Returns the error as a diagnostic along with the file it points into, or `None` if there is
+    /// no such file because the first file could not be read
+    pub fn diagnostic(&self) -> Option<(FileId, Diagnostic)> {
+        match self {
+            IncludeError::Read {
+                include: Some(include),
+                ..
+            } => Some((
+                include.file,
+                Diagnostic::error(self.to_string(), include.span).with_code(MISSING_INCLUDE),
+            )),
+            IncludeError::Read { include: None, .. } => None,
+            IncludeError::Cycle { include, .. } => Some((
+                include.file,
+                Diagnostic::error(self.to_string(), include.span).with_code(INCLUDE_CYCLE),
+            )),
+            IncludeError::Lex { file, error } => Some((
+                *file,
+                Diagnostic::error(error.to_string(), error.span).with_code(error.kind.code()),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for IncludeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IncludeError::Read {
+                path,
+                error,
+                include: Some(_),
+            } => write!(f, "Cannot include {}: {}", path.display(), error),
+            IncludeError::Read {
+                path,
+                error,
+                include: None,
+            } => write!(f, "Failed to read {}: {}", path.display(), error),
+            IncludeError::Cycle { path, .. } => write!(
+                f,
+                "Include cycle: {} is already being included",
+                path.display()
+            ),
+            IncludeError::Lex { error, .. } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for IncludeError {}
+
+/// Lexes the file at `path` along with every file it includes, recording them in `map`
+///
+/// Whitespace is kept as in `extract_tokens_with_spans`, except around the `include` lines, which
+/// are replaced by the tokens of the files they name. The files read before an error stay in
+/// `map`, so the error can be shown with `IncludeError::diagnostic`.
+pub fn lex_with_includes(
+    path: &Path,
+    map: &mut SourceMap,
+) -> Result<Vec<(Token, FileSpan)>, IncludeError> {
+    let mut includer = Includer {
+        map,
+        stack: Vec::new(),
+        files: HashMap::new(),
+    };
+    let mut tokens = Vec::new();
+    includer.splice(path, None, &mut tokens)?;
+
+    Ok(tokens)
+}
+
+struct Includer<'a> {
+    map: &'a mut SourceMap,
+    /// Canonical paths of the files being included, outermost first
+    stack: Vec<PathBuf>,
+    /// Ids of the files already read, by canonical path
+    files: HashMap<PathBuf, FileId>,
+}
+
+/// An `include` line found in a file
+struct Include {
+    /// Index of the `include` token, which is followed by whitespace and the path
+    index: usize,
+    path: String,
+    /// Span from `include` to the end of the path
+    span: Span,
+}
+
+impl Includer<'_> {
+    /// Appends the tokens of the file at `path` to `out`, with its includes spliced in
+    fn splice(
+        &mut self,
+        path: &Path,
+        include: Option<FileSpan>,
+        out: &mut Vec<(Token, FileSpan)>,
+    ) -> Result<(), IncludeError> {
+        let read_error = |error| IncludeError::Read {
+            path: path.to_path_buf(),
+            error,
+            include,
+        };
+        let canonical = fs::canonicalize(path).map_err(read_error)?;
+        if let Some(include) = include
+            && self.stack.contains(&canonical)
+        {
+            return Err(IncludeError::Cycle {
+                path: path.to_path_buf(),
+                include,
+            });
+        }
+
+        let file = match self.files.get(&canonical) {
+            Some(&file) => file,
+            None => {
+                let bytes = fs::read(path).map_err(read_error)?;
+                let source = match decode_source(&bytes) {
+                    Ok(source) => source.to_string(),
+                    Err(error) => {
+                        let valid = String::from_utf8_lossy(&bytes[..error.span.start]);
+                        let file = self.map.add(path, valid.into_owned());
+                        return Err(IncludeError::Lex { file, error });
+                    }
+                };
+                let file = self.map.add(path, source);
+                self.files.insert(canonical.clone(), file);
+                file
+            }
+        };
+
+        let source = self.map.source(file);
+        let tokens =
+            extract_tokens_with_spans(source).map_err(|error| IncludeError::Lex { file, error })?;
+        let includes = find_includes(source, &tokens);
+
+        let located =
+            |(token, span): &(Token, Span)| (token.clone(), FileSpan { file, span: *span });
+        self.stack.push(canonical);
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut next = 0;
+        for found in includes {
+            out.extend(tokens[next..found.index].iter().map(located));
+            let include = FileSpan {
+                file,
+                span: found.span,
+            };
+            self.splice(&directory.join(&found.path), Some(include), out)?;
+            next = found.index + 3;
+        }
+        out.extend(tokens[next..].iter().map(located));
+        self.stack.pop();
+
+        Ok(())
+    }
+}
+
+/// Returns the `include` lines of a file: an `include` identifier that starts a line, followed by
+/// whitespace and a string literal
+fn find_includes(source: &str, tokens: &[(Token, Span)]) -> Vec<Include> {
+    let starts_line = |index: usize| match index.checked_sub(1).map(|i| &tokens[i]) {
+        None => true,
+        Some((Token::Whitespace, span)) => {
+            span.start == 0 || source[span.start..span.end].contains('\n')
+        }
+        Some(_) => false,
+    };
+
+    tokens
+        .windows(3)
+        .enumerate()
+        .filter_map(|(index, window)| match window {
+            [
+                (Token::Identifier(name), start),
+                (Token::Whitespace, _),
+                (Token::StringLiteral(path), end),
+            ] if name == "include" && starts_line(index) => Some(Include {
+                index,
+                path: path.clone(),
+                span: Span::new(start.start, end.end),
+            }),
+            _ => None,
+        })
+        .collect()
+}
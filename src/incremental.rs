@@ -0,0 +1,73 @@
+//! # Incremental Lexing
+//!
+//! Updates a token list after an edit by re-lexing only the region around the change instead of
+//! the whole input, which is what an editor needs to stay responsive on every keystroke.
+//!
+//! Lexing restarts at the last run of whitespace that ends before the edit (no token can extend
+//! across whitespace, so everything before it is unaffected) and stops as soon as it produces a
+//! token identical to one of the old tokens past the edit, at the same shifted position. From
+//! that point on the input is unchanged, so the rest of the old list is reused with shifted spans.
+
+use crate::{LexError, Lexer, Span, Token};
+
+/// Tokens with their spans, including whitespace, as returned by `extract_tokens_with_spans`
+pub type TokenList = Vec<(Token, Span)>;
+
+/// Describes a text edit: `old_len` bytes at `start` were replaced by `new_len` bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub start: usize,
+    pub old_len: usize,
+    pub new_len: usize,
+}
+
+/// Returns the tokens of `new_text`, reusing `previous` (the tokens of `old_text`) where possible
+///
+/// The result is always the same as calling `extract_tokens_with_spans(new_text)`.
+pub fn relex(
+    previous: &TokenList,
+    old_text: &str,
+    new_text: &str,
+    edit: Edit,
+) -> Result<TokenList, LexError> {
+    debug_assert_eq!(
+        old_text.len() - edit.old_len + edit.new_len,
+        new_text.len(),
+        "edit does not describe the change between the texts"
+    );
+
+    let restart = previous
+        .iter()
+        .rposition(|(token, span)| matches!(token, Token::Whitespace) && span.end < edit.start)
+        .unwrap_or(0);
+    let restart_offset = previous.get(restart).map_or(0, |(_, span)| span.start);
+
+    let mut tokens: TokenList = previous[..restart].to_vec();
+    let old_edit_end = edit.start + edit.old_len;
+    let new_edit_end = edit.start + edit.new_len;
+
+    for result in Lexer::starting_at(new_text, restart_offset, true) {
+        let (token, span) = result?;
+
+        if span.start >= new_edit_end {
+            let old_start = span.start - edit.new_len + edit.old_len;
+            if let Ok(index) = previous.binary_search_by_key(&old_start, |(_, s)| s.start) {
+                let (old_token, old_span) = &previous[index];
+                if old_start >= old_edit_end
+                    && old_span.end - old_span.start == span.end - span.start
+                    && *old_token == token.to_owned()
+                {
+                    tokens.extend(previous[index..].iter().map(|(token, span)| {
+                        let shift = |offset: usize| offset - edit.old_len + edit.new_len;
+                        (token.clone(), Span::new(shift(span.start), shift(span.end)))
+                    }));
+                    return Ok(tokens);
+                }
+            }
+        }
+
+        tokens.push((token.to_owned(), span));
+    }
+
+    Ok(tokens)
+}
@@ -6,33 +6,132 @@
 //! ## Usage
 //! To use the lexical analyzer, set the `INPUT_FILE_PATH` constant to the desired location and run `cargo run` to execute the program.
 //!
+//! Alternatively, pass a file to lex with `cargo run -- <file>`. Passing several files or a directory
+//! (e.g. `cargo run -- submissions/ --ext lang`) lexes all of them with `batch::run_many` and prints a
+//! summary table instead, exiting with a failure status if any file could not be lexed.
+//!
+//! `cargo run -- diff <file_a> <file_b>` compares the tokens of two files with `diff::diff_tokens`,
+//! ignoring formatting, and exits with status 1 if they differ. Add `--ignore-identifier-names` to
+//! also ignore consistent renames.
+//!
+//! `cargo run -- check <path>...` only reports problems, without printing tokens: each diagnostic
+//! goes to stderr followed by a summary line, and the exit status is 1 if there are errors.
+//!
+//! `cargo run -- refs <file> <name>` prints the `line:col` of every occurrence of an identifier,
+//! found with `references::identifier_index`.
+//!
+//! `cargo run -- repl` starts an interactive prompt that prints the tokens of each entry.
+//!
+//! Add `--time` when lexing a single file to print how long each phase took to stderr; `run_timed`
+//! returns the same `timing::PhaseTimings`.
+//!
+//! A UTF-8 byte order mark and a `#!` line at the very start of the input are skipped, so scripts
+//! can be made executable. Spans still index the original input, so the `#!` line is line 1.
+//!
+//! `//` line comments and `/* */` block comments separate tokens like whitespace, and are part of
+//! the `Token::Whitespace` around them. `docs::extract_docs` pairs `///` and `/** */` comments
+//! with the function that follows them; `cargo run -- <file> --docs` prints them as JSON.
+//!
+//! A `-` written right before a digit is the sign of a number literal, so `-5` is `Integer(-5)`
+//! and `-9223372036854775808` (`i64::MIN`) is accepted, while `- 5` is `Operator::Minus` followed
+//! by `Integer(5)`. Unary `-` and `!` are separate tokens like any other operator, so they need
+//! whitespace before their operand: `- x` and `! done`, not `-x` or `!done`.
+//!
+//! A line starting with `include "path"` can splice in the tokens of another file:
+//! `include::lex_with_includes` lexes a file along with everything it includes, and
+//! `cargo run -- <file> --includes` prints the result.
+//!
+//! For inputs too large to read into memory, `stream::lex_reader` lexes from any `BufRead` a chunk
+//! at a time.
+//!
+//! ## WebAssembly
+//! The crate builds for `wasm32-unknown-unknown`, where the functions that read files and the
+//! `batch`, `cache`, `check` and `include` modules are left out. The `wasm` feature adds the `wasm` module, whose `lex_to_json`
+//! and `highlight` functions are exported to JavaScript with `wasm-bindgen`.
+//!
 //! ## Adding more keywords and operators
 //! To add a keyword or operator:
 //! - Add it to the `Keyword` or `Operator` enum.
 //! - Add an entry to the corresponding `phf_map!` static map.
 //! - If necessary, update the regular expressions in the `lexer!` macro.
+//! - For operators, give it a row in the semantic cube (`semantic_cube::operator_matrix`).
 //!
 //! ## Adding more tokens
 //! To add a new token:
 //! - Add a new variant to the `Token` enum.
 //! - Add a matching rule in the `lexer!` macro that maps input to the new token.
+//!
+//! ## Testing
+//! Each `tests/cases/<name>.src` file is lexed by `tests/snapshot_tests.rs` and compared against
+//! `tests/cases/<name>.tokens`. To add a case, write the `.src` file and run
+//! `UPDATE_SNAPSHOTS=1 cargo test` to generate its `.tokens` file, then review it.
+//!
+//! ## Error reporting
+//! `extract_tokens_with_spans` returns a `LexError` instead of panicking. Convert it into a
+//! `diagnostic::Diagnostic` and pass it to `diagnostic::render` to show the offending source line.
+//!
+//! Every kind of error has a stable code (`LexErrorKind::code`, e.g. `L0001`). With
+//! `--error-format json`, the CLI writes each diagnostic as a line of JSON from
+//! `diagnostic::to_json` for tools such as CI scripts.
+//!
+//! `lex_lossy` never stops at an error: it returns every token along with an error item for each
+//! part of the input it could not lex. It is fuzzed by the target in `fuzz/`
+//! (`cargo fuzz run lex_lossy`).
 
 use phf::phf_map;
 use plex::lexer;
+use std::borrow::Cow;
+use std::{fmt, io};
+#[cfg(not(target_arch = "wasm32"))]
 use std::{fs::File, io::Read};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod check;
+pub mod color;
+pub mod diagnostic;
+pub mod diff;
+pub mod docs;
+pub mod format;
+pub mod highlight;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod include;
+pub mod incremental;
+mod json;
+pub mod line_index;
+pub mod references;
+pub mod rename;
+pub mod repl;
+pub mod semantic_cube;
+pub mod semantic_tokens;
+pub mod stats;
+pub mod stream;
+pub mod suggest;
+pub mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod timing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 /// Represents supported keywords that the lexer can recognize
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Keyword {
     While,
     For,
     Fn,
     If,
     Else,
+    In,
+    /// Casts (e.g. `x as float`)
+    As,
 }
 
 /// Represents supported operators in the language
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Operator {
     // Arithmetic
     Plus,
@@ -58,24 +157,341 @@ pub enum Operator {
     And,
     Or,
     Not,
+
+    // Conditional (`cond ? a : b`)
+    Question,
+    Colon,
+
+    // Ranges (`start .. end`, `start ..= end`)
+    Range,
+    RangeInclusive,
+}
+
+/// Represents punctuation that delimits parts of an expression
+///
+/// Unlike other tokens, punctuation does not need whitespace around it, so `xs[0]` and
+/// `[1, 2]` are valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Punctuation {
+    LeftBracket,
+    RightBracket,
+    Comma,
 }
 
 /// Represents all possible tokens that can be produced by the lexer
+///
+/// Tokens are `Eq` and `Hash`, so they can key maps and sets. Decimals compare by the text they
+/// were written as (see `DecimalLit`).
 #[allow(dead_code)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Token {
-    /// Integer literals (e.g. `42`)
+    /// Integer literals, including their sign (e.g. `42`, `-7`)
     Integer(i64),
-    /// Whitespace characters (e.g. space, tab, newline)
+    /// Whitespace characters (e.g. space, tab, newline) and the comments among them
     Whitespace,
-    /// Identifiers (e.g. variable or function names)
+    /// Identifiers (e.g. variable or function names), which may contain Unicode letters
     Identifier(String),
-    /// Floating point literals (e.g. `3.14`)
-    Decimal(f64),
+    /// Floating point literals (e.g. `3.14`), keeping the text they were written as
+    Decimal(DecimalLit),
     /// Language keywords (e.g. `while`, `if`)
     Keyword(Keyword),
     /// Operators (e.g. `+`, `!=`)
     Operator(Operator),
+    /// String literals, holding the value of the string after escape sequences are replaced
+    /// (e.g. `"a\n"`, `r"C:\new"`)
+    StringLiteral(String),
+    /// String literals containing `${...}` segments (e.g. `"count = ${n + 1}"`)
+    InterpolatedString(Vec<StringPart>),
+    /// Brackets and commas (e.g. `[`, `,`)
+    Punctuation(Punctuation),
+}
+
+/// A decimal literal: its text as written in the source and the value it parses to
+///
+/// Two literals are equal when their text is, so `0.10` and `0.1` are different tokens even
+/// though `value` returns the same number for both. Comparing the text also makes equality
+/// reflexive, which comparing `f64` values is not.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DecimalLit {
+    lexeme: String,
+}
+
+impl DecimalLit {
+    /// Creates a literal from its text, or returns `None` if the text does not parse as an `f64`
+    pub fn new(lexeme: impl Into<String>) -> Option<Self> {
+        let lexeme = lexeme.into();
+        lexeme.parse::<f64>().ok()?;
+        Some(DecimalLit { lexeme })
+    }
+
+    /// Returns the text of the literal as written in the source
+    pub fn lexeme(&self) -> &str {
+        &self.lexeme
+    }
+
+    /// Returns the number the literal stands for
+    pub fn value(&self) -> f64 {
+        self.lexeme.parse().unwrap()
+    }
+}
+
+impl fmt::Debug for DecimalLit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lexeme)
+    }
+}
+
+impl fmt::Display for DecimalLit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.lexeme)
+    }
+}
+
+/// A piece of an interpolated string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StringPart {
+    /// Text between interpolations, after escape sequences are replaced
+    Literal(String),
+    /// The non-whitespace tokens of the expression in a `${...}` segment
+    Tokens(Vec<Token>),
+}
+
+/// The variant of a `Token`, without its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenKind {
+    Integer,
+    Whitespace,
+    Identifier,
+    Decimal,
+    Keyword,
+    Operator,
+    StringLiteral,
+    InterpolatedString,
+    Punctuation,
+}
+
+impl Token {
+    /// Returns the kind of this token
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            Token::Integer(_) => TokenKind::Integer,
+            Token::Whitespace => TokenKind::Whitespace,
+            Token::Identifier(_) => TokenKind::Identifier,
+            Token::Decimal(_) => TokenKind::Decimal,
+            Token::Keyword(_) => TokenKind::Keyword,
+            Token::Operator(_) => TokenKind::Operator,
+            Token::StringLiteral(_) => TokenKind::StringLiteral,
+            Token::InterpolatedString(_) => TokenKind::InterpolatedString,
+            Token::Punctuation(_) => TokenKind::Punctuation,
+        }
+    }
+}
+
+/// A token that borrows its text from the lexed input instead of owning it
+///
+/// Produced by `extract_tokens_borrowed`, which avoids allocating a `String` per identifier.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BorrowedToken<'a> {
+    Integer(i64),
+    Whitespace,
+    Identifier(&'a str),
+    /// The text of a decimal literal
+    Decimal(&'a str),
+    Keyword(Keyword),
+    Operator(Operator),
+    StringLiteral(Cow<'a, str>),
+    InterpolatedString(Vec<StringPart>),
+    Punctuation(Punctuation),
+}
+
+impl BorrowedToken<'_> {
+    /// Returns the kind of this token
+    pub fn kind(&self) -> TokenKind {
+        match self {
+            BorrowedToken::Integer(_) => TokenKind::Integer,
+            BorrowedToken::Whitespace => TokenKind::Whitespace,
+            BorrowedToken::Identifier(_) => TokenKind::Identifier,
+            BorrowedToken::Decimal(_) => TokenKind::Decimal,
+            BorrowedToken::Keyword(_) => TokenKind::Keyword,
+            BorrowedToken::Operator(_) => TokenKind::Operator,
+            BorrowedToken::StringLiteral(_) => TokenKind::StringLiteral,
+            BorrowedToken::InterpolatedString(_) => TokenKind::InterpolatedString,
+            BorrowedToken::Punctuation(_) => TokenKind::Punctuation,
+        }
+    }
+
+    /// Copies the token into an owned `Token`
+    pub fn to_owned(&self) -> Token {
+        match self {
+            BorrowedToken::Integer(value) => Token::Integer(*value),
+            BorrowedToken::Whitespace => Token::Whitespace,
+            BorrowedToken::Identifier(name) => Token::Identifier(name.to_string()),
+            BorrowedToken::Decimal(lexeme) => Token::Decimal(
+                DecimalLit::new(*lexeme).expect("the lexer only matches valid decimals"),
+            ),
+            BorrowedToken::Keyword(keyword) => Token::Keyword(keyword.clone()),
+            BorrowedToken::Operator(op) => Token::Operator(*op),
+            BorrowedToken::StringLiteral(value) => Token::StringLiteral(value.to_string()),
+            BorrowedToken::InterpolatedString(parts) => Token::InterpolatedString(parts.clone()),
+            BorrowedToken::Punctuation(punctuation) => Token::Punctuation(*punctuation),
+        }
+    }
+}
+
+/// Byte range `start..end` of the input covered by a token or an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Returns the span moved `offset` bytes further into the input
+    pub fn shifted(self, offset: usize) -> Span {
+        Span::new(self.start + offset, self.end + offset)
+    }
+
+    /// Returns the 1-based column of the start of the span in UTF-16 code units, as used by LSP
+    pub fn column_utf16(&self, index: &line_index::LineIndex) -> u32 {
+        index.column_utf16(self.start)
+    }
+}
+
+/// Represents the reasons why the lexer can reject its input
+#[derive(Debug, PartialEq)]
+pub enum LexErrorKind {
+    /// Two non-whitespace tokens appear one right after the other
+    MissingSeparator { previous: Token, next: Token },
+    /// No lexer rule matches the input starting at this character
+    UnrecognizedToken(char),
+    /// An integer literal does not fit in an `i64`
+    IntegerOverflow,
+    /// The operator rule matched text that is not in the operator table (e.g. a single `&`)
+    UnknownOperator(String),
+    /// A string literal is not closed before the end of the input
+    UnterminatedString,
+    /// A `/*` comment has no matching `*/`
+    UnterminatedComment,
+    /// A backslash in a string literal is followed by a character with no special meaning
+    InvalidEscape(char),
+    /// A `${` in a string literal has no matching `}`
+    UnterminatedInterpolation,
+    /// A `${}` in a string literal contains no expression
+    EmptyInterpolation,
+    /// Interpolations are nested in strings more than `MAX_INTERPOLATION_DEPTH` deep
+    InterpolationTooDeep,
+    /// The input is not valid UTF-8
+    InvalidUtf8,
+    /// The input could not be read
+    Io(io::ErrorKind),
+}
+
+impl LexErrorKind {
+    /// Returns a stable identifier for the kind of error, for tools that consume diagnostics
+    pub fn code(&self) -> &'static str {
+        match self {
+            LexErrorKind::UnrecognizedToken(_) => "L0001",
+            LexErrorKind::MissingSeparator { .. } => "L0002",
+            LexErrorKind::IntegerOverflow => "L0003",
+            LexErrorKind::UnknownOperator(_) => "L0004",
+            LexErrorKind::UnterminatedString => "L0005",
+            LexErrorKind::InvalidEscape(_) => "L0006",
+            LexErrorKind::UnterminatedInterpolation => "L0007",
+            LexErrorKind::EmptyInterpolation => "L0008",
+            LexErrorKind::InvalidUtf8 => "L0009",
+            LexErrorKind::Io(_) => "L0010",
+            LexErrorKind::UnterminatedComment => "L0011",
+            LexErrorKind::InterpolationTooDeep => "L0012",
+        }
+    }
+}
+
+/// Error produced when the input cannot be split into tokens
+#[derive(Debug, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LexErrorKind::MissingSeparator { previous, next } => write!(
+                f,
+                "Missing separator between tokens {:?} and {:?}",
+                previous, next
+            ),
+            LexErrorKind::UnrecognizedToken(c) => write!(
+                f,
+                "Unrecognized token starting at position {}: {:?}",
+                self.span.start, c
+            ),
+            LexErrorKind::IntegerOverflow => write!(
+                f,
+                "Integer literal at position {} is too large",
+                self.span.start
+            ),
+            LexErrorKind::UnknownOperator(op) => match suggest_operator(op) {
+                Some(suggestion) => write!(
+                    f,
+                    "Unknown operator `{}` at position {}; did you mean `{}`?",
+                    op, self.span.start, suggestion
+                ),
+                None => write!(
+                    f,
+                    "Operator `{}` at position {} matched by lexer but missing from table",
+                    op, self.span.start
+                ),
+            },
+            LexErrorKind::UnterminatedString => write!(
+                f,
+                "Unterminated string starting at position {}",
+                self.span.start
+            ),
+            LexErrorKind::UnterminatedComment => write!(
+                f,
+                "Unterminated block comment starting at position {}",
+                self.span.start
+            ),
+            LexErrorKind::InvalidEscape(c) => write!(
+                f,
+                "Invalid escape sequence `\\{}` in string starting at position {}",
+                c, self.span.start
+            ),
+            LexErrorKind::UnterminatedInterpolation => write!(
+                f,
+                "Unterminated interpolation starting at position {}",
+                self.span.start
+            ),
+            LexErrorKind::EmptyInterpolation => {
+                write!(f, "Empty interpolation at position {}", self.span.start)
+            }
+            LexErrorKind::InterpolationTooDeep => write!(
+                f,
+                "Interpolation at position {} is nested more than {} deep",
+                self.span.start, MAX_INTERPOLATION_DEPTH
+            ),
+            LexErrorKind::InvalidUtf8 => {
+                write!(f, "Invalid UTF-8 at position {}", self.span.start)
+            }
+            LexErrorKind::Io(kind) => write!(f, "Failed to read input: {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+/// Returns the operator that was most likely meant instead of an unknown one
+fn suggest_operator(op: &str) -> Option<&'static str> {
+    match op {
+        "&" => Some("&&"),
+        "|" => Some("||"),
+        _ => None,
+    }
 }
 
 /// Mapping of keyword strings to `Keyword` enum values
@@ -85,6 +501,8 @@ static KEYWORDS: phf::Map<&'static str, Keyword> = phf_map! {
     "fn" => Keyword::Fn,
     "if" => Keyword::If,
     "else" => Keyword::Else,
+    "in" => Keyword::In,
+    "as" => Keyword::As,
 };
 
 /// Mapping of operator strings to `Operator` enum values
@@ -113,12 +531,69 @@ static OPERATORS: phf::Map<&'static str, Operator> = phf_map! {
     "&&" => Operator::And,
     "||" => Operator::Or,
     "!" => Operator::Not,
+
+    // Conditional
+    "?" => Operator::Question,
+    ":" => Operator::Colon,
+
+    // Ranges
+    ".." => Operator::Range,
+    "..=" => Operator::RangeInclusive,
 };
 
+impl Keyword {
+    /// Returns the text of the keyword in source code
+    pub fn as_str(&self) -> &'static str {
+        KEYWORDS
+            .entries()
+            .find(|(_, keyword)| *keyword == self)
+            .map(|(text, _)| *text)
+            .expect("every keyword is in KEYWORDS")
+    }
+}
+
+impl Operator {
+    /// Returns the text of the operator in source code
+    pub fn as_str(&self) -> &'static str {
+        OPERATORS
+            .entries()
+            .find(|(_, op)| *op == self)
+            .map(|(text, _)| *text)
+            .expect("every operator is in OPERATORS")
+    }
+
+    /// Returns the operator a compound assignment applies before storing the result (e.g. `+` for
+    /// `+=`), or `None` if this is not a compound assignment
+    ///
+    /// `a op= b` means `a = a op b`, so passes can rewrite compound assignments in those terms
+    /// instead of handling each one.
+    pub fn compound_base(&self) -> Option<Operator> {
+        match self {
+            Operator::PlusEqual => Some(Operator::Plus),
+            Operator::MinusEqual => Some(Operator::Minus),
+            Operator::MultiplyEqual => Some(Operator::Multiply),
+            Operator::DivideEqual => Some(Operator::Divide),
+            _ => None,
+        }
+    }
+}
+
+impl Punctuation {
+    /// Returns the text of the punctuation in source code
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Punctuation::LeftBracket => "[",
+            Punctuation::RightBracket => "]",
+            Punctuation::Comma => ",",
+        }
+    }
+}
+
 /// Reads the contents of the file at the specified path
 ///
 /// # Panics
 /// Panics if the file cannot be opened or read.
+#[cfg(not(target_arch = "wasm32"))]
 pub fn extract_file_contents(file_name: &str) -> String {
     let mut file = File::open(file_name).expect("Failed to open file");
     let mut contents = String::new();
@@ -129,6 +604,23 @@ pub fn extract_file_contents(file_name: &str) -> String {
     contents
 }
 
+/// Interprets the bytes of a source as UTF-8, reporting the first invalid byte as an `InvalidUtf8`
+/// error
+///
+/// The bytes before the error's span are valid UTF-8, so they can be shown in a diagnostic.
+pub fn decode_source(bytes: &[u8]) -> Result<&str, LexError> {
+    std::str::from_utf8(bytes).map_err(|error| {
+        let start = error.valid_up_to();
+        LexError {
+            kind: LexErrorKind::InvalidUtf8,
+            span: Span::new(
+                start,
+                error.error_len().map_or(bytes.len(), |len| start + len),
+            ),
+        }
+    })
+}
+
 /// Tries to match a string slice to a known `Keyword`
 pub fn parse_keyword(s: &str) -> Option<Keyword> {
     KEYWORDS.get(s).cloned()
@@ -139,71 +631,867 @@ pub fn parse_operator(s: &str) -> Option<Operator> {
     OPERATORS.get(s).cloned()
 }
 
+/// Tries to match a string slice to a known `Punctuation`
+pub fn parse_punctuation(s: &str) -> Option<Punctuation> {
+    match s {
+        "[" => Some(Punctuation::LeftBracket),
+        "]" => Some(Punctuation::RightBracket),
+        "," => Some(Punctuation::Comma),
+        _ => None,
+    }
+}
+
 // Lexer definition that converts input strings into tokens
 lexer! {
-    fn take_token(tok: 'a) -> Token;
+    fn take_token(tok: 'a) -> Result<BorrowedToken<'a>, LexErrorKind>;
 
-    r"[ \n\t]+" => Token::Whitespace,
-    r"-?[0-9]+\.[0-9]+" => Token::Decimal(tok.parse().unwrap()),
-    r"-?[0-9]+" => Token::Integer(tok.parse().unwrap()),
-    r"\+=|-=|\*=|/=|==|!=|<=|>=|\&\&|\|\||[+\\\-*\/%<>!=]" => {
+    r"-?[0-9]+\.[0-9]+" => Ok(BorrowedToken::Decimal(tok)),
+    r"-?[0-9]+" => match tok.parse() {
+        Ok(value) => Ok(BorrowedToken::Integer(value)),
+        Err(_) => Err(LexErrorKind::IntegerOverflow),
+    },
+    r"\+=|-=|\*=|/=|==|!=|<=|>=|\&\&|\|\||\.\.=|\.\.|[+\\\-*\/%<>!=\&\|?:]" => {
         if let Some(op) = parse_operator(tok) {
-            Token::Operator(op)
+            Ok(BorrowedToken::Operator(op))
         } else {
-            panic!("Unknown operator: {}", tok);
+            Err(LexErrorKind::UnknownOperator(tok.to_string()))
         }
     }
+    r"\[" => Ok(BorrowedToken::Punctuation(Punctuation::LeftBracket)),
+    r"\]" => Ok(BorrowedToken::Punctuation(Punctuation::RightBracket)),
+    "," => Ok(BorrowedToken::Punctuation(Punctuation::Comma)),
+    // Identifiers with non-ASCII characters are lexed by `next_token` instead
     "[a-zA-Z_][a-zA-Z0-9_]*" => {
         if let Some(keyword) = parse_keyword(tok) {
-            Token::Keyword(keyword)
+            Ok(BorrowedToken::Keyword(keyword))
         } else {
-            Token::Identifier(String::from(tok))
+            Ok(BorrowedToken::Identifier(tok))
         }
     }
 }
 
+/// Lexes the token at the start of the input, trying strings and identifiers with non-ASCII
+/// characters before the rules of `take_token`
+///
+/// The span of an error is relative to the start of the input.
+fn next_token(input: &str) -> Option<(Result<BorrowedToken<'_>, LexError>, &str)> {
+    if let Some(result) = raw_string(input).or_else(|| quoted_string(input)) {
+        return Some(match result {
+            Ok((token, len)) => (Ok(token), &input[len..]),
+            Err(error) => (Err(error), ""),
+        });
+    }
+
+    let len = unicode_identifier_len(input);
+    if len > 0 && !input[..len].is_ascii() {
+        return Some((Ok(BorrowedToken::Identifier(&input[..len])), &input[len..]));
+    }
+
+    let (token, remaining) = take_token(input)?;
+    let token = token.map_err(|kind| LexError {
+        kind,
+        span: Span::new(0, input.len() - remaining.len()),
+    });
+    Some((token, remaining))
+}
+
+/// Result of lexing a string: the token and its length in bytes, or an error with a span relative
+/// to the start of the string
+///
+/// An error ends the lexing of the whole input, so its span may cover less than the string.
+type StringResult<'a> = Result<(BorrowedToken<'a>, usize), LexError>;
+
+/// Lexes a raw string at the start of the input
+///
+/// A raw string is `r"..."`, where backslashes have no special meaning, or `r#"..."#` with any
+/// number of `#`, which ends only at a `"` followed by the same number of `#`. Returns `None` if
+/// the input does not start with a raw string.
+fn raw_string(input: &str) -> Option<StringResult<'_>> {
+    let hashes = input
+        .strip_prefix('r')?
+        .bytes()
+        .take_while(|&b| b == b'#')
+        .count();
+    let open_len = 1 + hashes + 1;
+    if input.as_bytes().get(open_len - 1) != Some(&b'"') {
+        return None;
+    }
+
+    let closing = format!("\"{}", "#".repeat(hashes));
+    let Some(contents_len) = input[open_len..].find(&closing) else {
+        return Some(Err(LexError {
+            kind: LexErrorKind::UnterminatedString,
+            span: Span::new(0, input.len()),
+        }));
+    };
+
+    let contents = &input[open_len..open_len + contents_len];
+    Some(Ok((
+        BorrowedToken::StringLiteral(Cow::Borrowed(contents)),
+        open_len + contents_len + closing.len(),
+    )))
+}
+
+/// Lexes a string in double quotes at the start of the input
+///
+/// Strings may span several lines. A backslash starts an escape sequence (`\n`, `\t`, `\r`,
+/// `\0`, `\\`, `\"` or `\$`), and a backslash at the end of a line removes the line break along
+/// with the spaces and tabs that start the next line. Each `${...}` is lexed as an expression,
+/// which turns the string into a `Token::InterpolatedString`. Returns `None` if the input does not
+/// start with `"`.
+fn quoted_string(input: &str) -> Option<StringResult<'_>> {
+    let body = input.strip_prefix('"')?;
+    // Only filled in once an escape sequence or interpolation is found, until then the value is
+    // borrowed
+    let mut value: Option<String> = None;
+    let mut parts = Vec::new();
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => {
+                let len = 1 + i + 1;
+                if parts.is_empty() {
+                    let value = value.map_or(Cow::Borrowed(&body[..i]), Cow::Owned);
+                    return Some(Ok((BorrowedToken::StringLiteral(value), len)));
+                }
+
+                parts.extend(
+                    value
+                        .filter(|text| !text.is_empty())
+                        .map(StringPart::Literal),
+                );
+                return Some(Ok((BorrowedToken::InterpolatedString(parts), len)));
+            }
+            '$' if chars.next_if(|&(_, c)| c == '{').is_some() => {
+                let text = value.take().unwrap_or_else(|| body[..i].to_string());
+                if !text.is_empty() {
+                    parts.push(StringPart::Literal(text));
+                }
+                value = Some(String::new());
+
+                let segment_start = 1 + i;
+                let (tokens, len) = match interpolation(&input[segment_start..]) {
+                    Ok(segment) => segment,
+                    Err(error) => {
+                        return Some(Err(LexError {
+                            span: error.span.shifted(segment_start),
+                            ..error
+                        }));
+                    }
+                };
+                parts.push(StringPart::Tokens(tokens));
+
+                let end = i + len;
+                while chars.next_if(|&(j, _)| j < end).is_some() {}
+            }
+            '\\' => {
+                let value = value.get_or_insert_with(|| body[..i].to_string());
+                let Some((j, escaped)) = chars.next() else {
+                    break;
+                };
+                match escaped {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    '0' => value.push('\0'),
+                    '\\' | '"' | '$' => value.push(escaped),
+                    '\n' | '\r' => {
+                        if escaped == '\r' {
+                            chars.next_if(|&(_, c)| c == '\n');
+                        }
+                        while chars.next_if(|&(_, c)| c == ' ' || c == '\t').is_some() {}
+                    }
+                    _ => {
+                        return Some(Err(LexError {
+                            kind: LexErrorKind::InvalidEscape(escaped),
+                            span: Span::new(0, 1 + j + escaped.len_utf8()),
+                        }));
+                    }
+                }
+            }
+            _ => {
+                if let Some(value) = &mut value {
+                    value.push(c);
+                }
+            }
+        }
+    }
+
+    Some(Err(LexError {
+        kind: LexErrorKind::UnterminatedString,
+        span: Span::new(0, input.len()),
+    }))
+}
+
+/// Number of interpolations that may be nested inside each other through their strings
+///
+/// Each level is lexed by a recursive call, so without a limit a long run of `"${` would overflow
+/// the stack.
+pub const MAX_INTERPOLATION_DEPTH: usize = 32;
+
+/// Lexes the `${...}` segment at the start of the input, returning the tokens of the expression
+/// and the length of the segment in bytes
+///
+/// The segment ends at the `}` matching its `{`. Braces inside nested strings are skipped, so they
+/// do not end the segment early.
+fn interpolation(input: &str) -> Result<(Vec<Token>, usize), LexError> {
+    let expression_start = 2;
+    let expression_end = expression_end(input, 1)?;
+
+    let expression = &input[expression_start..expression_end];
+    let len = expression_end + 1;
+    if expression.trim().is_empty() {
+        return Err(LexError {
+            kind: LexErrorKind::EmptyInterpolation,
+            span: Span::new(0, len),
+        });
+    }
+
+    let tokens = Lexer::new(expression, false)
+        .without_prefix()
+        .map(|item| match item {
+            Ok((token, _)) => Ok(token.to_owned()),
+            Err(error) => Err(LexError {
+                span: error.span.shifted(expression_start),
+                ..error
+            }),
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((tokens, len))
+}
+
+/// Returns the offset of the `}` that ends the `${...}` segment at the start of the input
+///
+/// Nested strings are only skipped over here, not lexed, so that finding the end of a segment
+/// takes time proportional to its length however deeply its strings nest. `depth` is the number
+/// of interpolations the segment is nested in, itself included.
+fn expression_end(input: &str, depth: usize) -> Result<usize, LexError> {
+    if depth > MAX_INTERPOLATION_DEPTH {
+        return Err(LexError {
+            kind: LexErrorKind::InterpolationTooDeep,
+            span: Span::new(0, 2),
+        });
+    }
+
+    let mut depth_in_braces = 0;
+    let mut offset = 2;
+
+    loop {
+        let rest = &input[offset..];
+        let Some(c) = rest.chars().next() else {
+            return Err(LexError {
+                kind: LexErrorKind::UnterminatedInterpolation,
+                span: Span::new(0, input.len()),
+            });
+        };
+
+        // Strings are only looked for at the start of a token, so `bar"` is not a raw string
+        let after_identifier = input[..offset]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c == '_' || unicode_ident::is_xid_continue(c));
+        let string_len = match c {
+            '"' => quoted_string_len(rest, depth),
+            'r' if !after_identifier => raw_string(rest).map(|result| result.map(|(_, len)| len)),
+            _ => None,
+        };
+
+        match (c, string_len) {
+            (_, Some(Ok(len))) => {
+                offset += len;
+                continue;
+            }
+            (_, Some(Err(error))) => {
+                return Err(LexError {
+                    span: error.span.shifted(offset),
+                    ..error
+                });
+            }
+            ('{', None) => depth_in_braces += 1,
+            ('}', None) if depth_in_braces == 0 => return Ok(offset),
+            ('}', None) => depth_in_braces -= 1,
+            _ => {}
+        }
+        offset += c.len_utf8();
+    }
+}
+
+/// Returns the length in bytes of the string in double quotes at the start of the input, without
+/// lexing it, or `None` if the input does not start with `"`
+///
+/// `depth` is the number of interpolations the string is nested in.
+fn quoted_string_len(input: &str, depth: usize) -> Option<Result<usize, LexError>> {
+    let body = input.strip_prefix('"')?;
+    let mut offset = 0;
+
+    while let Some(c) = body[offset..].chars().next() {
+        match c {
+            '"' => return Some(Ok(1 + offset + 1)),
+            '\\' => {
+                let Some(escaped) = body[offset + 1..].chars().next() else {
+                    break;
+                };
+                offset += 1 + escaped.len_utf8();
+            }
+            '$' if body[offset + 1..].starts_with('{') => {
+                match expression_end(&body[offset..], depth + 1) {
+                    Ok(end) => offset += end + 1,
+                    Err(error) => {
+                        return Some(Err(LexError {
+                            span: error.span.shifted(1 + offset),
+                            ..error
+                        }));
+                    }
+                }
+            }
+            _ => offset += c.len_utf8(),
+        }
+    }
+
+    Some(Err(LexError {
+        kind: LexErrorKind::UnterminatedString,
+        span: Span::new(0, input.len()),
+    }))
+}
+
+/// Returns the length in bytes of the identifier at the start of the input, or 0 if there is none
+///
+/// Identifiers follow Unicode UAX #31: an `XID_Start` character or `_`, followed by any number of
+/// `XID_Continue` characters.
+fn unicode_identifier_len(input: &str) -> usize {
+    let mut chars = input.char_indices();
+    match chars.next() {
+        Some((_, c)) if c == '_' || unicode_ident::is_xid_start(c) => {}
+        _ => return 0,
+    }
+
+    chars
+        .find(|&(_, c)| !unicode_ident::is_xid_continue(c))
+        .map_or(input.len(), |(i, _)| i)
+}
+
 /// Extracts all tokens from the input string using the lexer
 ///
 /// # Panics
-/// Panics if two non-whitespace tokens are found without a valid separator between them.
+/// Panics if two non-whitespace tokens are found without a valid separator between them, or if
+/// part of the input does not match any token.
 pub fn extract_tokens(input: String) -> Vec<Token> {
-    let mut remaining = input.as_str();
-    let mut tokens: Vec<Token> = Vec::new();
+    match extract_tokens_with_spans(&input) {
+        Ok(tokens) => tokens.into_iter().map(|(token, _)| token).collect(),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Extracts all tokens from the input string along with the byte range each one covers
+///
+/// Unlike `extract_tokens`, lexing errors are returned instead of causing a panic.
+pub fn extract_tokens_with_spans(input: &str) -> Result<Vec<(Token, Span)>, LexError> {
+    lex(input, &LexerOptions::default())
+}
+
+/// Extracts all non-whitespace tokens from the input string
+///
+/// Whitespace still separates tokens, but is never added to the output.
+///
+/// # Panics
+/// Panics under the same conditions as `extract_tokens`.
+pub fn extract_tokens_filtered(input: &str) -> Vec<Token> {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    match lex(input, &options) {
+        Ok(tokens) => tokens.into_iter().map(|(token, _)| token).collect(),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Settings that change what the lexer outputs
+#[derive(Debug, Clone)]
+pub struct LexerOptions {
+    /// Whether runs of whitespace and comments are returned as `Token::Whitespace`
+    pub emit_whitespace: bool,
+}
+
+impl Default for LexerOptions {
+    fn default() -> Self {
+        LexerOptions {
+            emit_whitespace: true,
+        }
+    }
+}
+
+/// Extracts all tokens from the input string along with their spans, using the given options
+pub fn extract_tokens_with_options(
+    input: &str,
+    options: &LexerOptions,
+) -> Result<Vec<(Token, Span)>, LexError> {
+    lex(input, options)
+}
+
+/// Extracts all tokens like `extract_tokens_with_options`, calling `on_token` for each token as
+/// soon as it is lexed
+///
+/// The callback sees every run of whitespace, even when `options.emit_whitespace` is `false`.
+pub fn extract_tokens_traced(
+    input: &str,
+    options: &LexerOptions,
+    mut on_token: impl FnMut(&Token, Span),
+) -> Result<Vec<(Token, Span)>, LexError> {
+    let mut tokens = Vec::new();
+
+    for item in Lexer::new(input, true) {
+        let (token, span) = item?;
+        let token = token.to_owned();
+        on_token(&token, span);
+        if options.emit_whitespace || token != Token::Whitespace {
+            tokens.push((token, span));
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Extracts all tokens from the input string without copying identifiers out of it
+///
+/// # Panics
+/// Panics under the same conditions as `extract_tokens`.
+pub fn extract_tokens_borrowed(input: &str) -> Vec<BorrowedToken<'_>> {
+    match lex_borrowed(input, &LexerOptions::default()) {
+        Ok(tokens) => tokens.into_iter().map(|(token, _)| token).collect(),
+        Err(error) => panic!("{}", error),
+    }
+}
+
+/// Returns the token covering the byte at `offset`, lexing only the line it is on
+///
+/// Returns `None` if the offset is in whitespace or a comment, at or past the end of the input, or
+/// if its line cannot be lexed up to the offset. An offset in the middle of a multi-byte character
+/// refers to that character.
+///
+/// Since lexing starts at the beginning of the line, errors on earlier lines make no difference.
+/// For the same reason, a string or block comment that starts on an earlier line is not seen: the
+/// text of its later lines is lexed as if it were code.
+pub fn lex_at(source: &str, offset: usize) -> Option<(Token, Span)> {
+    if offset >= source.len() {
+        return None;
+    }
+    let line_start = source.as_bytes()[..offset]
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+
+    for result in Lexer::starting_at(source, line_start, false) {
+        let (token, span) = result.ok()?;
+        if span.end > offset {
+            return (span.start <= offset).then(|| (token.to_owned(), span));
+        }
+    }
+
+    None
+}
+
+/// An entry produced by `lex_lossy`
+#[derive(Debug, PartialEq)]
+pub enum LexItem {
+    /// A token, including runs of whitespace
+    Token { token: Token, span: Span },
+    /// A part of the input that could not be turned into a token
+    Error { span: Span, reason: LexErrorKind },
+}
+
+impl LexItem {
+    /// Returns the range of the input covered by this item
+    pub fn span(&self) -> Span {
+        match self {
+            LexItem::Token { span, .. } | LexItem::Error { span, .. } => *span,
+        }
+    }
+}
+
+/// Lexes the whole input, turning every problem into an error item instead of stopping
+///
+/// Never panics. Characters that start no token are skipped one at a time, and a token that
+/// follows another one without a separator is reported as an error in place of the token. The
+/// spans of the items cover the whole input, in order, without gaps or overlaps.
+pub fn lex_lossy(input: &str) -> Vec<LexItem> {
+    let mut items = Vec::new();
+    let mut offset = 0;
+    // Last token produced, if nothing has been seen since
+    let mut previous: Option<BorrowedToken> = None;
+
+    while offset < input.len() {
+        let whitespace_start = offset;
+        if offset == 0 {
+            offset = prefix_len(input);
+        }
+        let trivia = trivia_end(input, offset);
+        offset = match trivia {
+            Ok(end) => end,
+            Err(ref error) => error.span.start,
+        };
+        if offset > whitespace_start {
+            previous = None;
+            items.push(LexItem::Token {
+                token: Token::Whitespace,
+                span: Span::new(whitespace_start, offset),
+            });
+        }
+        if let Err(error) = trivia {
+            items.push(LexItem::Error {
+                span: error.span,
+                reason: error.kind,
+            });
+            break;
+        }
+        if offset > whitespace_start {
+            continue;
+        }
+
+        let remaining = &input[offset..];
+        let Some((token, new_remaining)) = next_token(remaining) else {
+            let offending = remaining.chars().next().unwrap_or_default();
+            let span = Span::new(offset, offset + offending.len_utf8());
+            previous = None;
+            items.push(LexItem::Error {
+                span,
+                reason: LexErrorKind::UnrecognizedToken(offending),
+            });
+            offset = span.end;
+            continue;
+        };
+        let span = Span::new(offset, input.len() - new_remaining.len());
+        offset = span.end;
+
+        let token = match token {
+            Ok(token) => token,
+            Err(error) => {
+                previous = None;
+                items.push(LexItem::Error {
+                    span,
+                    reason: error.kind,
+                });
+                continue;
+            }
+        };
+
+        if let Some(previous) = previous.replace(token.clone())
+            && needs_separator(previous.kind(), token.kind())
+        {
+            items.push(LexItem::Error {
+                span,
+                reason: LexErrorKind::MissingSeparator {
+                    previous: previous.to_owned(),
+                    next: token.to_owned(),
+                },
+            });
+        } else {
+            items.push(LexItem::Token {
+                token: token.to_owned(),
+                span,
+            });
+        }
+    }
+
+    items
+}
+
+/// Returns `true` if two tokens of these kinds must have whitespace between them, which is the
+/// case unless one of them is punctuation
+pub(crate) fn needs_separator(previous: TokenKind, next: TokenKind) -> bool {
+    previous != TokenKind::Punctuation && next != TokenKind::Punctuation
+}
+
+/// Characters that separate tokens: space, newline, tab, carriage return, vertical tab and form feed
+///
+/// Only `\n` starts a new line, so `\r\n` counts as a single line break and a lone `\r` is
+/// ordinary whitespace one column wide.
+const WHITESPACE: [u8; 6] = [b' ', b'\n', b'\t', b'\r', 0x0b, 0x0c];
+
+/// Returns the end of the run of whitespace and comments starting at `offset`
+///
+/// A `//` comment ends before its line break, and a `/* */` comment at the first `*/`, so block
+/// comments do not nest. An unterminated block comment is an error spanning the rest of the input.
+fn trivia_end(input: &str, mut offset: usize) -> Result<usize, LexError> {
+    let bytes = input.as_bytes();
 
-    while let Some((token, new_remaining)) = take_token(remaining) {
-        if let Some(prev_token) = tokens.last() {
-            if !matches!(prev_token, Token::Whitespace) && !matches!(token, Token::Whitespace) {
-                panic!(
-                    "Missing separator between tokens {:?} and {:?}",
-                    prev_token, token
-                )
+    loop {
+        while offset < bytes.len() && WHITESPACE.contains(&bytes[offset]) {
+            offset += 1;
+        }
+        match comment_len(&input[offset..]) {
+            Some(Ok(len)) => offset += len,
+            Some(Err(error)) => {
+                return Err(LexError {
+                    span: error.span.shifted(offset),
+                    ..error
+                });
             }
+            None => return Ok(offset),
         }
+    }
+}
+
+/// Returns the length of the comment at the start of the input, or `None` if it does not start
+/// with one
+///
+/// The span of an error is relative to the start of the input.
+pub(crate) fn comment_len(input: &str) -> Option<Result<usize, LexError>> {
+    if input.starts_with("//") {
+        return Some(Ok(input.find('\n').unwrap_or(input.len())));
+    }
+
+    let body = input.strip_prefix("/*")?;
+    Some(match body.find("*/") {
+        Some(len) => Ok(2 + len + 2),
+        None => Err(LexError {
+            kind: LexErrorKind::UnterminatedComment,
+            span: Span::new(0, input.len()),
+        }),
+    })
+}
 
-        tokens.push(token);
-        remaining = new_remaining;
+/// Returns the spans of the comments in a run of whitespace and comments, such as a
+/// `Token::Whitespace`
+pub(crate) fn comment_spans(source: &str, span: Span) -> Vec<Span> {
+    // The byte order mark and `#!` line are not comments, even if they contain `//`
+    let mut offset = match span.start {
+        0 => prefix_len(source).min(span.end),
+        start => start,
+    };
+    let mut spans = Vec::new();
+
+    while offset < span.end {
+        match comment_len(&source[offset..span.end]) {
+            Some(Ok(len)) => {
+                spans.push(Span::new(offset, offset + len));
+                offset += len;
+            }
+            _ => offset += source[offset..].chars().next().map_or(1, char::len_utf8),
+        }
     }
 
-    if !remaining.trim().is_empty() {
-        let position = input.len() - remaining.len();
-        panic!(
-            "Unrecognized token starting at position {}: {:?}",
-            position, remaining
-        );
+    spans
+}
+
+/// Returns the length of the byte order mark and the `#!` line at the start of the input, if any
+///
+/// The `#!` line is recognized only as the first two characters of the input (after the byte
+/// order mark), and does not include its line break.
+pub(crate) fn prefix_len(input: &str) -> usize {
+    let bom = if input.starts_with('\u{feff}') { 3 } else { 0 };
+    let rest = &input[bom..];
+
+    if rest.starts_with("#!") {
+        bom + rest.find('\n').unwrap_or(rest.len())
+    } else {
+        bom
     }
+}
+
+/// Lexes the input into owned tokens
+fn lex(input: &str, options: &LexerOptions) -> Result<Vec<(Token, Span)>, LexError> {
+    let tokens = lex_borrowed(input, options)?;
+    Ok(tokens
+        .into_iter()
+        .map(|(token, span)| (token.to_owned(), span))
+        .collect())
+}
+
+/// Lexes the input into tokens borrowing from it
+fn lex_borrowed<'a>(
+    input: &'a str,
+    options: &LexerOptions,
+) -> Result<Vec<(BorrowedToken<'a>, Span)>, LexError> {
+    Lexer::new(input, options.emit_whitespace).collect()
+}
 
-    tokens
+/// Produces tokens one at a time by advancing a byte offset into the input
+///
+/// Runs of whitespace and comments are consumed here rather than by `take_token`, so when
+/// `emit_whitespace` is `false` no `Token::Whitespace` is ever created. To detect missing separators, only the last
+/// token is remembered, and it is forgotten as soon as whitespace is seen.
+///
+/// A byte order mark and a `#!` line at the start of the input are skipped as part of the first
+/// run of whitespace.
+pub(crate) struct Lexer<'a> {
+    input: &'a str,
+    offset: usize,
+    emit_whitespace: bool,
+    /// Whether offset 0 is the start of the whole input rather than of a part of it
+    skip_prefix: bool,
+    /// Last token produced, if no whitespace has been seen since
+    previous: Option<(BorrowedToken<'a>, Span)>,
+    finished: bool,
 }
 
-/// Main function: reads input, tokenizes it, and prints each token (excluding whitespace)
-pub fn run(input_file: &str) {
-    let s = extract_file_contents(input_file);
-    let tokens = extract_tokens(s);
+impl<'a> Lexer<'a> {
+    pub(crate) fn new(input: &'a str, emit_whitespace: bool) -> Self {
+        Lexer::starting_at(input, 0, emit_whitespace)
+    }
 
+    /// Creates a lexer that resumes at `offset`, which must be the start of a token or of a run
+    /// of whitespace that follows a token
+    pub(crate) fn starting_at(input: &'a str, offset: usize, emit_whitespace: bool) -> Self {
+        Lexer {
+            input,
+            offset,
+            emit_whitespace,
+            skip_prefix: true,
+            previous: None,
+            finished: false,
+        }
+    }
+
+    /// Treats the input as a part of a larger input that does not start at offset 0
+    pub(crate) fn without_prefix(mut self) -> Self {
+        self.skip_prefix = false;
+        self
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<(BorrowedToken<'a>, Span), LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let whitespace_start = self.offset;
+        if self.offset == 0 && self.skip_prefix {
+            self.offset = prefix_len(self.input);
+        }
+        self.offset = match trivia_end(self.input, self.offset) {
+            Ok(end) => end,
+            // Return the whitespace before an unterminated comment first, and the error on the
+            // next call
+            Err(error) if self.emit_whitespace && error.span.start > whitespace_start => {
+                error.span.start
+            }
+            Err(error) => {
+                self.finished = true;
+                return Some(Err(error));
+            }
+        };
+        if self.offset > whitespace_start {
+            self.previous = None;
+            if self.emit_whitespace {
+                let span = Span::new(whitespace_start, self.offset);
+                return Some(Ok((BorrowedToken::Whitespace, span)));
+            }
+        }
+
+        let remaining = &self.input[self.offset..];
+        let Some((token, new_remaining)) = next_token(remaining) else {
+            self.finished = true;
+            if remaining.trim().is_empty() {
+                return None;
+            }
+
+            let offending = remaining.chars().next().unwrap_or_default();
+            return Some(Err(LexError {
+                kind: LexErrorKind::UnrecognizedToken(offending),
+                span: Span::new(self.offset, self.offset + offending.len_utf8()),
+            }));
+        };
+        let span = Span::new(self.offset, self.input.len() - new_remaining.len());
+        let token = match token {
+            Ok(token) => token,
+            Err(error) => {
+                self.finished = true;
+                return Some(Err(LexError {
+                    span: error.span.shifted(self.offset),
+                    ..error
+                }));
+            }
+        };
+
+        if let Some((previous, prev_span)) = self.previous.take()
+            && needs_separator(previous.kind(), token.kind())
+        {
+            self.finished = true;
+            return Some(Err(LexError {
+                kind: LexErrorKind::MissingSeparator {
+                    previous: previous.to_owned(),
+                    next: token.to_owned(),
+                },
+                span: Span::new(prev_span.start, span.end),
+            }));
+        }
+
+        self.previous = Some((token.clone(), span));
+        self.offset = span.end;
+        Some(Ok((token, span)))
+    }
+}
+
+/// Reads the file at the specified path and extracts its tokens, including whitespace
+///
+/// # Panics
+/// Panics if the file cannot be opened or read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run(input_file: &str) -> Result<Vec<Token>, LexError> {
+    run_with_timings(input_file, None)
+}
+
+/// Runs like `run`, also returning how long reading and lexing the file took
+///
+/// # Panics
+/// Panics if the file cannot be opened or read.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_timed(input_file: &str) -> (Result<Vec<Token>, LexError>, timing::PhaseTimings) {
+    let mut timings = timing::PhaseTimings::new();
+    let result = run_with_timings(input_file, Some(&mut timings));
+
+    (result, timings)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn run_with_timings(
+    input_file: &str,
+    mut timings: Option<&mut timing::PhaseTimings>,
+) -> Result<Vec<Token>, LexError> {
+    let s = timing::time_phase(
+        timings.as_deref_mut(),
+        timing::Phase::Read,
+        || extract_file_contents(input_file),
+        String::len,
+    );
+    let tokens = timing::time_phase(
+        timings,
+        timing::Phase::Lex,
+        || extract_tokens_with_spans(&s),
+        |tokens| tokens.as_ref().map_or(0, Vec::len),
+    )?;
+
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Settings for `print_tokens`
+#[derive(Debug, Clone, Default)]
+pub struct PrintOptions {
+    /// Whether `Token::Whitespace` is printed along with the other tokens
+    pub include_whitespace: bool,
+    /// Whether each token is colored by its kind (see `color`)
+    pub color: bool,
+}
+
+/// Writes each token on its own line as `Token: <token>`
+pub fn print_tokens<W: io::Write>(
+    tokens: &[Token],
+    out: &mut W,
+    opts: &PrintOptions,
+) -> io::Result<()> {
     for tok in tokens {
-        if matches!(tok, Token::Whitespace) {
+        if *tok == Token::Whitespace && !opts.include_whitespace {
             continue;
         }
-        println!("Token: {:?}", tok);
+        let style = opts.color.then(|| color::token_style(tok.kind())).flatten();
+        writeln!(out, "Token: {}", color::paint(&format!("{:?}", tok), style))?;
     }
+
+    Ok(())
 }
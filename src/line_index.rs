@@ -0,0 +1,104 @@
+//! # Line Index
+//!
+//! Converts between byte offsets and 1-based line and column numbers. The start of every line is
+//! found once when the index is built, so each lookup is a binary search instead of a scan of the
+//! source.
+//!
+//! Columns are counted in characters, so a multi-byte character such as `é` takes one column.
+//! `column_utf16` counts UTF-16 code units instead, as LSP clients do by default. Lines end at
+//! `\n`: the `\r` of a `\r\n` ending belongs to its line but is left out of `line_text`.
+
+use std::ops::Range;
+
+/// Line start offsets of a source, for converting offsets to lines and columns and back
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset of the first character of each line
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        LineIndex {
+            source,
+            line_starts,
+        }
+    }
+
+    /// Returns the number of lines, counting the empty line after a trailing newline
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the 1-based line and column of a byte offset
+    ///
+    /// Offsets past the end of the source are treated as the end of the source, and an offset in
+    /// the middle of a character refers to that character.
+    pub fn line_col(&self, offset: usize) -> (u32, u32) {
+        let (line, before) = self.locate(offset);
+        (line as u32 + 1, before.chars().count() as u32 + 1)
+    }
+
+    /// Returns the 1-based column of a byte offset counted in UTF-16 code units, so characters
+    /// outside the Basic Multilingual Plane (such as most emoji) take two columns
+    ///
+    /// Offsets are clamped like in `line_col`. Subtract 1 for the `character` of an LSP
+    /// `Position`.
+    pub fn column_utf16(&self, offset: usize) -> u32 {
+        let (_, before) = self.locate(offset);
+        before.encode_utf16().count() as u32 + 1
+    }
+
+    /// Returns the 0-based line of an offset and the text of that line before it
+    fn locate(&self, offset: usize) -> (usize, &'a str) {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+
+        (line, &self.source[self.line_starts[line]..offset])
+    }
+
+    /// Returns the byte offset of a 1-based line and column, or `None` if the line does not exist
+    /// or is shorter than the column
+    ///
+    /// The column just past the last character of a line is valid and refers to its line ending.
+    pub fn offset(&self, line: u32, column: u32) -> Option<usize> {
+        let range = self.line_range(line)?;
+        let column = (column as usize).checked_sub(1)?;
+
+        self.source[range.clone()]
+            .char_indices()
+            .map(|(i, _)| range.start + i)
+            .chain(std::iter::once(range.end))
+            .nth(column)
+    }
+
+    /// Returns the text of a 1-based line without its line ending, or an empty string if the line
+    /// does not exist
+    pub fn line_text(&self, line: u32) -> &'a str {
+        self.line_range(line)
+            .map_or("", |range| &self.source[range])
+    }
+
+    /// Returns the byte range of a 1-based line without its line ending
+    pub fn line_range(&self, line: u32) -> Option<Range<usize>> {
+        let index = (line as usize).checked_sub(1)?;
+        let start = *self.line_starts.get(index)?;
+        let end = match self.line_starts.get(index + 1) {
+            Some(&next) if next - 1 > start && self.source.as_bytes()[next - 2] == b'\r' => {
+                next - 2
+            }
+            Some(&next) => next - 1,
+            None => self.source.len(),
+        };
+
+        Some(start..end)
+    }
+}
@@ -1,6 +1,627 @@
-/// Path to the input file to be analyzed
+use compiler_project_tc3002_b::color::ColorChoice;
+use compiler_project_tc3002_b::diagnostic::{self, Diagnostic, RenderOptions};
+use compiler_project_tc3002_b::include::{self, SourceMap};
+use compiler_project_tc3002_b::line_index::LineIndex;
+use compiler_project_tc3002_b::timing::{self, Phase, PhaseTimings};
+use compiler_project_tc3002_b::{
+    LexError, LexItem, LexerOptions, PrintOptions, Span, Token, batch, check, decode_source, diff,
+    docs, extract_tokens_traced, extract_tokens_with_spans, highlight, lex_lossy, print_tokens,
+    references, rename, repl, stats, suggest, table,
+};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::{fs, io};
+
+/// Path to the input file to be analyzed when no paths are given
 const INPUT_FILE_PATH: &str = "./tests/Lex_InputFile.txt";
 
-fn main() {
-    compiler_project_tc3002_b::run(INPUT_FILE_PATH);
+/// How the tokens of a single file are printed
+#[derive(Clone, Copy)]
+enum Format {
+    /// One `Token: ...` line per token
+    List,
+    /// An aligned table with the position, kind and lexeme of each token
+    Table,
+    /// The source highlighted as HTML
+    Html,
+    /// The documented functions as JSON
+    Docs,
+}
+
+/// How diagnostics are written to stderr
+#[derive(Clone, Copy)]
+enum ErrorFormat {
+    /// The offending source line with the problem underlined
+    Human,
+    /// One JSON object per line, see `diagnostic::to_json`
+    Json,
+}
+
+/// Settings for lexing a single file
+#[derive(Clone, Copy)]
+struct Options {
+    format: Format,
+    error_format: ErrorFormat,
+    /// Print each token to stderr as soon as it is lexed
+    trace: bool,
+    /// Print token statistics after the tokens
+    stats: bool,
+    /// Splice in the tokens of the files named by `include` lines
+    includes: bool,
+    /// Print how long each phase took to stderr
+    time: bool,
+    color: ColorChoice,
+}
+
+/// Usage:
+/// - `compiler_project_tc3002_b` lexes `INPUT_FILE_PATH` and prints its tokens.
+/// - `compiler_project_tc3002_b <file>` lexes a single file and prints its tokens.
+/// - `compiler_project_tc3002_b [--ext <ext>]... <path> <path>...` lexes every file (searching
+///   directories recursively for the given extensions) and prints a summary table.
+/// - `compiler_project_tc3002_b diff <file_a> <file_b> [--ignore-identifier-names]
+///   [--error-format human|json] [--color=auto|always|never]` compares the tokens of two files,
+///   exiting with 1 if they differ or either has a lex error.
+/// - `compiler_project_tc3002_b check [--ext <ext>]... [--error-format human|json]
+///   [--deny-warnings] [--color=auto|always|never] <path>...` reports the problems in every file without printing tokens,
+///   followed by a summary line. Warnings only fail the check with `--deny-warnings`.
+/// - `compiler_project_tc3002_b refs <file> <name>` prints the `line:col` of every occurrence of
+///   an identifier, exiting with 1 if there are none.
+/// - `compiler_project_tc3002_b rename <file> <old> <new> [--in-place]` prints the file with an
+///   identifier renamed, or rewrites the file with `--in-place`.
+/// - `compiler_project_tc3002_b repl` lexes lines typed at a prompt.
+///
+/// Options for a single file:
+/// - `--format list|table|html` prints the tokens as a list (the default) or an aligned table, or
+///   the source as HTML.
+/// - `--trace` prints each token to stderr as soon as it is lexed (list format only).
+/// - `--stats` prints token statistics after the tokens (list format only).
+/// - `--includes` splices in the tokens of the files named by `include "path"` lines (list format
+///   only).
+/// - `--time` prints how long reading and lexing the file took to stderr.
+/// - `--color=auto|always|never` colors tokens by kind (list and table formats) and diagnostics
+///   by severity. `auto` (the default) colors only output to a terminal, unless `NO_COLOR` is set.
+/// - `--docs` prints the doc comments of each function as JSON instead of the tokens.
+/// - `--error-format human|json` writes diagnostics as source excerpts (the default) or as one
+///   JSON object per line.
+///
+/// Exits with 0 on success, 1 if there are errors in the input (or the files differ, for
+/// `diff`), and 2 for an invalid command line or a file that cannot be read.
+fn main() -> ExitCode {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut extensions: Vec<String> = Vec::new();
+    let mut options = Options {
+        format: Format::List,
+        error_format: ErrorFormat::Human,
+        trace: false,
+        stats: false,
+        includes: false,
+        time: false,
+        color: ColorChoice::Auto,
+    };
+
+    let mut args = std::env::args().skip(1).peekable();
+    if args.next_if_eq("diff").is_some() {
+        return diff_command(args.collect());
+    }
+    if args.next_if_eq("check").is_some() {
+        return check_command(args.collect());
+    }
+    if args.next_if_eq("refs").is_some() {
+        return refs_command(args.collect());
+    }
+    if args.next_if_eq("rename").is_some() {
+        return rename_command(args.collect());
+    }
+    if args.next_if_eq("repl").is_some() {
+        return match repl::run(io::stdin().lock(), io::stdout().lock()) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("{}", error);
+                ExitCode::from(2)
+            }
+        };
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ext" => match args.next() {
+                Some(ext) => extensions.push(ext.trim_start_matches('.').to_string()),
+                None => return usage_error("Missing value for --ext"),
+            },
+            "--format" => match args.next().as_deref() {
+                Some("list") => options.format = Format::List,
+                Some("table") => options.format = Format::Table,
+                Some("html") => options.format = Format::Html,
+                _ => return usage_error("Expected `list`, `table` or `html` after --format"),
+            },
+            "--error-format" => match args.next().as_deref() {
+                Some("human") => options.error_format = ErrorFormat::Human,
+                Some("json") => options.error_format = ErrorFormat::Json,
+                _ => return usage_error("Expected `human` or `json` after --error-format"),
+            },
+            "--trace" => options.trace = true,
+            "--stats" => options.stats = true,
+            "--includes" => options.includes = true,
+            "--time" => options.time = true,
+            "--docs" => options.format = Format::Docs,
+            _ if arg.starts_with("--color=") => {
+                match ColorChoice::parse(&arg["--color=".len()..]) {
+                    Some(choice) => options.color = choice,
+                    None => {
+                        return usage_error("Expected `auto`, `always` or `never` after --color=");
+                    }
+                }
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    let single = match paths.as_slice() {
+        [] => Some(Path::new(INPUT_FILE_PATH)),
+        [path] if path.is_file() => Some(path.as_path()),
+        _ => None,
+    };
+    let list_only = options.trace || options.stats;
+    if list_only && (single.is_none() || !matches!(options.format, Format::List)) {
+        return usage_error("--trace and --stats only work on a single file with --format list");
+    }
+    if options.time && (single.is_none() || options.includes) {
+        return usage_error("--time only works on a single file, without --includes");
+    }
+    if options.includes
+        && (single.is_none() || !matches!(options.format, Format::List) || list_only)
+    {
+        return usage_error(
+            "--includes only works on a single file with --format list, without --trace or --stats",
+        );
+    }
+
+    match single {
+        Some(path) if options.includes => lex_with_includes(path, options),
+        Some(path) if options.time => {
+            let mut timings = PhaseTimings::new();
+            let code = lex_single(path, options, Some(&mut timings));
+            eprint!("{}", timings);
+            code
+        }
+        Some(path) => lex_single(path, options, None),
+        None => {
+            let mut batch_options = batch::BatchOptions::default();
+            if !extensions.is_empty() {
+                batch_options.extensions = extensions;
+            }
+            let reports = batch::run_many_with_options(&paths, &batch_options);
+
+            print!("{}", batch::summary_table(&reports));
+            let mut errors = reports.iter().flat_map(|report| &report.errors);
+            if errors.any(|error| matches!(error, batch::FileError::Io(_))) {
+                return ExitCode::from(2);
+            }
+            if reports.iter().any(|report| !report.is_ok()) {
+                return ExitCode::FAILURE;
+            }
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Lexes a single file and prints it in the given format, warning about identifiers that look
+/// like misspelled keywords
+///
+/// The phases are recorded in `timings` if it is given.
+fn lex_single(path: &Path, options: Options, mut timings: Option<&mut PhaseTimings>) -> ExitCode {
+    let source = timing::time_phase(
+        timings.as_deref_mut(),
+        Phase::Read,
+        || read_source(path, options.error_format, options.color),
+        |source| source.as_ref().map_or(0, String::len),
+    );
+    let source = match source {
+        Ok(source) => source,
+        Err(code) => return code,
+    };
+    let render_options = render_options(options.color);
+    let color = options.color.enabled_for(&io::stdout());
+    let report = |diag: Diagnostic| match options.error_format {
+        ErrorFormat::Human => eprint!(
+            "{}",
+            diagnostic::render_with_options(&diag, &source, &render_options)
+        ),
+        ErrorFormat::Json => eprintln!(
+            "{}",
+            diagnostic::to_json(&diag, &path.to_string_lossy(), &source)
+        ),
+    };
+
+    let warn_misspellings = |tokens: &[(Token, Span)]| {
+        for suggestion in suggest::suggest_keywords(tokens) {
+            report(suggestion.to_diagnostic());
+        }
+    };
+
+    let tokens = timing::time_phase(
+        timings,
+        Phase::Lex,
+        || lex_source(&source, options.trace),
+        |tokens| tokens.as_ref().map_or(0, Vec::len),
+    );
+    let tokens = match tokens {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            // The tokens before the error can still contain misspelled keywords
+            let before: Vec<(Token, Span)> = lex_lossy(&source)
+                .into_iter()
+                .map_while(|item| match item {
+                    LexItem::Token { token, span } if span.end <= error.span.start => {
+                        Some((token, span))
+                    }
+                    _ => None,
+                })
+                .collect();
+            warn_misspellings(&before);
+            report(error.into());
+            return ExitCode::FAILURE;
+        }
+    };
+    warn_misspellings(&tokens);
+
+    match options.format {
+        Format::List => {
+            let tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+            let print_options = PrintOptions {
+                color,
+                ..PrintOptions::default()
+            };
+            if let Err(error) = print_tokens(&tokens, &mut io::stdout().lock(), &print_options) {
+                eprintln!("Failed to print tokens: {}", error);
+                return ExitCode::FAILURE;
+            }
+
+            if options.stats {
+                let mut token_stats = stats::token_stats(&tokens);
+                token_stats.lines_of_code = Some(stats::lines_of_code(&source));
+                print!("\n{}", token_stats);
+            }
+        }
+        Format::Table => print!(
+            "{}",
+            table::format_table(
+                &source,
+                &tokens,
+                &table::TableOptions {
+                    color,
+                    ..table::TableOptions::default()
+                }
+            )
+        ),
+        Format::Docs => print!(
+            "{}",
+            docs::docs_to_json(&docs::docs_from_tokens(&source, &tokens))
+        ),
+        Format::Html => match highlight::highlight_html(&source) {
+            Ok(html) => println!("<pre><code>{}</code></pre>", html),
+            Err(error) => {
+                report(error.into());
+                return ExitCode::FAILURE;
+            }
+        },
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Lexes a file along with the files it includes and prints their tokens
+fn lex_with_includes(path: &Path, options: Options) -> ExitCode {
+    let mut map = SourceMap::new();
+    let tokens = match include::lex_with_includes(path, &mut map) {
+        Ok(tokens) => tokens,
+        Err(error) => {
+            let Some((file, diag)) = error.diagnostic() else {
+                eprintln!("{}", error);
+                return ExitCode::from(2);
+            };
+            let name = map.path(file).to_string_lossy();
+            let source = map.source(file);
+            match options.error_format {
+                ErrorFormat::Human => eprint!(
+                    "{}",
+                    diagnostic::render_in_file(
+                        &diag,
+                        &name,
+                        source,
+                        &render_options(options.color)
+                    )
+                ),
+                ErrorFormat::Json => eprintln!("{}", diagnostic::to_json(&diag, &name, source)),
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+    if let Err(error) = print_tokens(&tokens, &mut io::stdout().lock(), &PrintOptions::default()) {
+        eprintln!("Failed to print tokens: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Lexes the source, printing each token to stderr as it is lexed if `trace` is set
+fn lex_source(source: &str, trace: bool) -> Result<Vec<(Token, Span)>, LexError> {
+    if !trace {
+        return extract_tokens_with_spans(source);
+    }
+
+    extract_tokens_traced(source, &LexerOptions::default(), |token, span| {
+        eprintln!(
+            "{}..{} {:?} {:?}",
+            span.start,
+            span.end,
+            token.kind(),
+            &source[span.start..span.end]
+        );
+    })
+}
+
+/// Checks every file for problems, printing only diagnostics and a summary
+fn check_command(args: Vec<String>) -> ExitCode {
+    let mut paths = Vec::new();
+    let mut extensions: Vec<String> = Vec::new();
+    let mut error_format = ErrorFormat::Human;
+    let mut deny_warnings = false;
+    let mut color = ColorChoice::Auto;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ext" => match args.next() {
+                Some(ext) => extensions.push(ext.trim_start_matches('.').to_string()),
+                None => return usage_error("Missing value for --ext"),
+            },
+            "--error-format" => match args.next().as_deref() {
+                Some("human") => error_format = ErrorFormat::Human,
+                Some("json") => error_format = ErrorFormat::Json,
+                _ => return usage_error("Expected `human` or `json` after --error-format"),
+            },
+            "--deny-warnings" => deny_warnings = true,
+            _ if arg.starts_with("--color=") => {
+                match ColorChoice::parse(&arg["--color=".len()..]) {
+                    Some(choice) => color = choice,
+                    None => {
+                        return usage_error("Expected `auto`, `always` or `never` after --color=");
+                    }
+                }
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        return usage_error(
+            "Usage: check [--ext <ext>]... [--error-format human|json] [--deny-warnings] \
+             [--color=auto|always|never] <path>...",
+        );
+    }
+    if extensions.is_empty() {
+        extensions = batch::BatchOptions::default().extensions;
+    }
+
+    let (mut files, mut unreadable) = batch::expand_paths(&paths, &extensions);
+    files.sort();
+    let mut reports = Vec::new();
+    for path in files {
+        match check::check(&path) {
+            Ok(report) => reports.push(report),
+            Err(error) => unreadable.push((path, error)),
+        }
+    }
+
+    for (path, error) in &unreadable {
+        eprintln!("Failed to read {}: {}", path.display(), error);
+    }
+    let render_options = render_options(color);
+    for report in &reports {
+        let name = report.path.to_string_lossy();
+        for diag in &report.diagnostics {
+            match error_format {
+                ErrorFormat::Human => eprint!(
+                    "{}",
+                    diagnostic::render_in_file(diag, &name, &report.source, &render_options)
+                ),
+                ErrorFormat::Json => {
+                    eprintln!("{}", diagnostic::to_json(diag, &name, &report.source))
+                }
+            }
+        }
+    }
+    eprintln!("{}", check::summary(&reports, unreadable.len()));
+
+    if !unreadable.is_empty() {
+        ExitCode::from(2)
+    } else if reports
+        .iter()
+        .all(|report| report.is_ok() && !(deny_warnings && report.warning_count() > 0))
+    {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Prints where an identifier occurs in a file
+fn refs_command(args: Vec<String>) -> ExitCode {
+    let [file, name] = args.as_slice() else {
+        return usage_error("Usage: refs <file> <name>");
+    };
+    let source = match read_source(Path::new(file), ErrorFormat::Human, ColorChoice::Auto) {
+        Ok(source) => source,
+        Err(code) => return code,
+    };
+
+    let index = match references::identifier_index(&source) {
+        Ok(index) => index,
+        Err(error) => {
+            eprint!("{}", diagnostic::render(&error.into(), &source));
+            return ExitCode::FAILURE;
+        }
+    };
+    let lines = LineIndex::new(&source);
+    for span in index.occurrences(name) {
+        let (line, column) = lines.line_col(span.start);
+        println!("{}:{}", line, column);
+    }
+
+    if index.count(name) == 0 {
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Renames an identifier in a file, printing the result or writing it back to the file
+fn rename_command(args: Vec<String>) -> ExitCode {
+    let in_place = args.iter().any(|arg| arg == "--in-place");
+    let args: Vec<&String> = args.iter().filter(|arg| *arg != "--in-place").collect();
+    let [file, old, new] = args.as_slice() else {
+        return usage_error("Usage: rename <file> <old> <new> [--in-place]");
+    };
+    let source = match read_source(Path::new(file), ErrorFormat::Human, ColorChoice::Auto) {
+        Ok(source) => source,
+        Err(code) => return code,
+    };
+
+    let renamed = match rename::rename_identifier(&source, old, new) {
+        Ok(renamed) => renamed,
+        Err(rename::RenameError::Lex(error)) => {
+            eprint!("{}", diagnostic::render(&error.into(), &source));
+            return ExitCode::FAILURE;
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Ok(index) = references::identifier_index(&source)
+        && index.count(new) > 0
+    {
+        eprintln!("warning: `{}` already occurs in {}", new, file);
+    }
+
+    if !in_place {
+        print!("{}", renamed);
+        return ExitCode::SUCCESS;
+    }
+    match fs::write(file, renamed) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Failed to write {}: {}", file, error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Compares the tokens of two files and prints the differences
+fn diff_command(args: Vec<String>) -> ExitCode {
+    let mut options = diff::DiffOptions::default();
+    let mut error_format = ErrorFormat::Human;
+    let mut color = ColorChoice::Auto;
+    let mut files = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ignore-identifier-names" => options.ignore_identifier_names = true,
+            "--error-format" => match args.next().as_deref() {
+                Some("human") => error_format = ErrorFormat::Human,
+                Some("json") => error_format = ErrorFormat::Json,
+                _ => return usage_error("Expected `human` or `json` after --error-format"),
+            },
+            _ if arg.starts_with("--color=") => {
+                match ColorChoice::parse(&arg["--color=".len()..]) {
+                    Some(choice) => color = choice,
+                    None => {
+                        return usage_error("Expected `auto`, `always` or `never` after --color=");
+                    }
+                }
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    let [a_name, b_name] = files.as_slice() else {
+        return usage_error(
+            "Usage: diff <file_a> <file_b> [--ignore-identifier-names] \
+             [--error-format human|json] [--color=auto|always|never]",
+        );
+    };
+    let (a, b) = match (
+        read_source(Path::new(a_name), error_format, color),
+        read_source(Path::new(b_name), error_format, color),
+    ) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(code), _) | (_, Err(code)) => return code,
+    };
+
+    match diff::diff_tokens_with_options(&a, &b, &options) {
+        Ok(result) if result.is_empty() => ExitCode::SUCCESS,
+        Ok(result) => {
+            print!("{}", diff::format_diff(&result, a_name, &a, b_name, &b));
+            ExitCode::FAILURE
+        }
+        Err(error) => {
+            let (name, source, diag) = match error {
+                diff::DiffError::First(error) => (a_name, &a, Diagnostic::from(error)),
+                diff::DiffError::Second(error) => (b_name, &b, Diagnostic::from(error)),
+            };
+            match error_format {
+                ErrorFormat::Human => eprint!(
+                    "{}",
+                    diagnostic::render_in_file(&diag, name, source, &render_options(color))
+                ),
+                ErrorFormat::Json => eprintln!("{}", diagnostic::to_json(&diag, name, source)),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads a source file, reporting a failure with exit code 2 and invalid UTF-8 as a diagnostic
+/// with exit code 1
+fn read_source(
+    path: &Path,
+    error_format: ErrorFormat,
+    color: ColorChoice,
+) -> Result<String, ExitCode> {
+    let bytes = fs::read(path).map_err(|error| {
+        eprintln!("Failed to read {}: {}", path.display(), error);
+        ExitCode::from(2)
+    })?;
+
+    match decode_source(&bytes) {
+        Ok(source) => Ok(source.to_string()),
+        Err(error) => {
+            let name = path.to_string_lossy();
+            let valid = String::from_utf8_lossy(&bytes[..error.span.start]);
+            let diag = Diagnostic::from(error);
+            match error_format {
+                ErrorFormat::Human => eprint!(
+                    "{}",
+                    diagnostic::render_in_file(&diag, &name, &valid, &render_options(color))
+                ),
+                ErrorFormat::Json => eprintln!("{}", diagnostic::to_json(&diag, &name, &valid)),
+            }
+            Err(ExitCode::FAILURE)
+        }
+    }
+}
+
+/// Returns the settings for rendering diagnostics to stderr
+fn render_options(color: ColorChoice) -> RenderOptions {
+    RenderOptions {
+        color: color.enabled_for(&io::stderr()),
+        ..RenderOptions::default()
+    }
+}
+
+/// Reports an invalid command line
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("{}", message);
+    ExitCode::from(2)
 }
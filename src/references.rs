@@ -0,0 +1,98 @@
+//! # Identifier References
+//!
+//! Indexes every occurrence of each identifier in a source, which is the basis for finding
+//! references and renaming. Keywords are not identifiers, so they are never indexed.
+//!
+//! Without a parser the only definitions that can be told apart are function names: an identifier
+//! right after `fn` is a definition, and every other occurrence is a use. Identifiers inside the
+//! `${...}` of interpolated strings are not indexed, as their tokens carry no spans.
+
+use crate::{Keyword, LexError, Span, Token, extract_tokens_with_spans};
+use std::collections::HashMap;
+
+/// Spans of every occurrence of each identifier in a source
+#[derive(Debug, Clone, Default)]
+pub struct IdentifierIndex {
+    names: HashMap<String, Occurrences>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Occurrences {
+    /// Every occurrence, in source order
+    all: Vec<Span>,
+    /// The occurrences that name a function being defined, in source order
+    definitions: Vec<Span>,
+}
+
+impl IdentifierIndex {
+    /// Returns the spans of every occurrence of `name` in source order, or an empty slice if it
+    /// does not occur
+    pub fn occurrences(&self, name: &str) -> &[Span] {
+        self.names
+            .get(name)
+            .map_or(&[], |occurrences| &occurrences.all)
+    }
+
+    /// Returns the spans where `name` is defined as a function
+    pub fn definitions(&self, name: &str) -> &[Span] {
+        self.names
+            .get(name)
+            .map_or(&[], |occurrences| &occurrences.definitions)
+    }
+
+    /// Returns the spans where `name` occurs other than as a definition
+    pub fn uses<'a>(&'a self, name: &str) -> impl Iterator<Item = Span> + 'a {
+        let definitions = self.definitions(name);
+        self.occurrences(name)
+            .iter()
+            .copied()
+            .filter(move |span| !definitions.contains(span))
+    }
+
+    /// Returns the number of occurrences of `name`
+    pub fn count(&self, name: &str) -> usize {
+        self.occurrences(name).len()
+    }
+
+    /// Returns every identifier that occurs, in no particular order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.keys().map(String::as_str)
+    }
+
+    /// Returns the number of distinct identifiers
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Lexes the source and indexes its identifiers
+pub fn identifier_index(source: &str) -> Result<IdentifierIndex, LexError> {
+    Ok(index_tokens(&extract_tokens_with_spans(source)?))
+}
+
+/// Indexes the identifiers of an already lexed source
+pub fn index_tokens(tokens: &[(Token, Span)]) -> IdentifierIndex {
+    let mut index = IdentifierIndex::default();
+    let mut after_fn = false;
+
+    for (token, span) in tokens {
+        match token {
+            Token::Whitespace => continue,
+            Token::Identifier(name) => {
+                let occurrences = index.names.entry(name.clone()).or_default();
+                occurrences.all.push(*span);
+                if after_fn {
+                    occurrences.definitions.push(*span);
+                }
+            }
+            _ => {}
+        }
+        after_fn = *token == Token::Keyword(Keyword::Fn);
+    }
+
+    index
+}
@@ -0,0 +1,100 @@
+//! # Rename
+//!
+//! Renames an identifier by rewriting the text of each of its tokens, leaving every other byte of
+//! the source (whitespace, comments, and string literals that happen to contain the name) exactly
+//! as it was.
+
+use crate::references::index_tokens;
+use crate::{LexError, StringPart, Token, extract_tokens_with_spans, parse_keyword};
+use std::fmt;
+
+/// Represents the reasons why an identifier cannot be renamed
+#[derive(Debug, PartialEq)]
+pub enum RenameError {
+    /// The source could not be split into tokens
+    Lex(LexError),
+    /// The new name is a keyword, so the renamed identifiers would turn into keywords
+    WouldShadowKeyword(String),
+    /// The new name would not lex as a single identifier
+    InvalidIdentifier(String),
+    /// The identifier occurs inside the `${...}` of an interpolated string, whose tokens have no
+    /// spans to rewrite; renaming only the other occurrences would change what the program means
+    InInterpolation(String),
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::Lex(error) => write!(f, "{}", error),
+            RenameError::WouldShadowKeyword(name) => {
+                write!(f, "Cannot rename to `{}`, which is a keyword", name)
+            }
+            RenameError::InvalidIdentifier(name) => {
+                write!(f, "Cannot rename to `{}`, which is not an identifier", name)
+            }
+            RenameError::InInterpolation(name) => write!(
+                f,
+                "Cannot rename `{}`, which is used inside a string interpolation",
+                name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+impl From<LexError> for RenameError {
+    fn from(error: LexError) -> Self {
+        RenameError::Lex(error)
+    }
+}
+
+/// Returns the source with every `old` identifier replaced by `new`
+pub fn rename_identifier(source: &str, old: &str, new: &str) -> Result<String, RenameError> {
+    validate_identifier(new)?;
+    let tokens = extract_tokens_with_spans(source)?;
+    if tokens
+        .iter()
+        .any(|(token, _)| interpolates_identifier(token, old))
+    {
+        return Err(RenameError::InInterpolation(old.to_string()));
+    }
+
+    let mut renamed = String::with_capacity(source.len());
+    let mut offset = 0;
+    for span in index_tokens(&tokens).occurrences(old) {
+        renamed.push_str(&source[offset..span.start]);
+        renamed.push_str(new);
+        offset = span.end;
+    }
+    renamed.push_str(&source[offset..]);
+
+    Ok(renamed)
+}
+
+/// Checks that `name` lexes as a single identifier
+fn validate_identifier(name: &str) -> Result<(), RenameError> {
+    if parse_keyword(name).is_some() {
+        return Err(RenameError::WouldShadowKeyword(name.to_string()));
+    }
+
+    match extract_tokens_with_spans(name).as_deref() {
+        Ok([(Token::Identifier(lexed), _)]) if lexed == name => Ok(()),
+        _ => Err(RenameError::InvalidIdentifier(name.to_string())),
+    }
+}
+
+/// Returns `true` if the token is an interpolated string using the identifier `name`
+fn interpolates_identifier(token: &Token, name: &str) -> bool {
+    let Token::InterpolatedString(parts) = token else {
+        return false;
+    };
+
+    parts.iter().any(|part| match part {
+        StringPart::Literal(_) => false,
+        StringPart::Tokens(tokens) => tokens.iter().any(|token| {
+            matches!(token, Token::Identifier(identifier) if identifier == name)
+                || interpolates_identifier(token, name)
+        }),
+    })
+}
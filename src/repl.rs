@@ -0,0 +1,124 @@
+//! # REPL
+//!
+//! Lexes each entry typed at a prompt and prints its tokens, showing errors without leaving the
+//! loop. An entry continues on the next line (with a `..` prompt) while it ends with an operator,
+//! has a `[` that is not closed yet, or has an unterminated string, interpolation or block comment;
+//! an empty line ends it anyway.
+//!
+//! Lines starting with `:` are commands, listed by `:help`.
+
+use crate::diagnostic::{self, Diagnostic};
+use crate::{
+    LexErrorKind, LexerOptions, PrintOptions, Punctuation, Token, extract_tokens_with_options,
+};
+use std::io::{self, BufRead, Write};
+
+/// Prompt shown at the start of an entry
+pub const PROMPT: &str = ">> ";
+
+/// Prompt shown on the following lines of an entry
+pub const CONTINUATION_PROMPT: &str = ".. ";
+
+const HELP: &str = "\
+Enter code to see its tokens. Commands:
+  :help  show this message
+  :eval  evaluate entries instead (not available yet)
+  :quit  leave the REPL
+";
+
+/// Runs the REPL until `:quit` or the end of the input
+pub fn run(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    // Lines of the entry being typed
+    let mut entry = String::new();
+    let mut lines = input.lines();
+
+    loop {
+        let prompt = if entry.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        write!(output, "{}", prompt)?;
+        output.flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            // Show what is wrong with an unfinished entry before leaving
+            if !entry.is_empty() {
+                writeln!(output)?;
+                print_entry(&entry, &mut output)?;
+            }
+            return Ok(());
+        };
+
+        if entry.is_empty() {
+            match line.trim() {
+                ":quit" => return Ok(()),
+                ":help" => {
+                    write!(output, "{}", HELP)?;
+                    continue;
+                }
+                ":eval" => {
+                    writeln!(output, "There is no evaluator yet; entries are only lexed")?;
+                    continue;
+                }
+                command if command.starts_with(':') => {
+                    writeln!(output, "Unknown command `{}`; try :help", command)?;
+                    continue;
+                }
+                _ => {}
+            }
+        } else {
+            entry.push('\n');
+        }
+
+        entry.push_str(&line);
+        if line.trim().is_empty() || !needs_continuation(&entry) {
+            print_entry(&entry, &mut output)?;
+            entry.clear();
+        }
+    }
+}
+
+/// Returns `true` if the entry ends with an operator, has more `[` than `]`, or has an
+/// unterminated string, interpolation or block comment
+fn needs_continuation(entry: &str) -> bool {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    match extract_tokens_with_options(entry, &options) {
+        Ok(tokens) => {
+            let open_brackets = tokens.iter().fold(0, |depth, (token, _)| match token {
+                Token::Punctuation(Punctuation::LeftBracket) => depth + 1,
+                Token::Punctuation(Punctuation::RightBracket) => depth - 1,
+                _ => depth,
+            });
+            open_brackets > 0 || matches!(tokens.last(), Some((Token::Operator(_), _)))
+        }
+        Err(error) => matches!(
+            error.kind,
+            LexErrorKind::UnterminatedString
+                | LexErrorKind::UnterminatedInterpolation
+                | LexErrorKind::UnterminatedComment
+        ),
+    }
+}
+
+/// Prints the tokens of an entry, or the error that stops it from being lexed
+fn print_entry(entry: &str, output: &mut impl Write) -> io::Result<()> {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    match extract_tokens_with_options(entry, &options) {
+        Ok(tokens) => {
+            let tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token).collect();
+            crate::print_tokens(&tokens, output, &PrintOptions::default())
+        }
+        Err(error) => write!(
+            output,
+            "{}",
+            diagnostic::render(&Diagnostic::from(error), entry)
+        ),
+    }
+}
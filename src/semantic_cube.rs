@@ -0,0 +1,168 @@
+//! # Semantic Cube
+//!
+//! Describes which operand types each `Operator` accepts and the type it produces.
+//!
+//! Every operator maps to a static 4×4 matrix indexed as `[lhs][rhs]` by `Type`. The lookup in
+//! `operator_matrix` is an exhaustive `match`, so adding a new `Operator` variant without giving it
+//! a row here is a compile error.
+//!
+//! Compound assignments have no matrix of their own: `lhs op= rhs` is checked as
+//! `lhs = lhs op rhs`, using `Operator::compound_base`. Since the result is stored back into the
+//! left operand, it may not widen it (`Int += Float` is not allowed).
+
+use crate::Operator;
+
+/// Represents the primitive types of the language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    Float,
+    Bool,
+    String,
+}
+
+impl Type {
+    /// Every type, in the order used to index the cube
+    pub const ALL: [Type; 4] = [Type::Int, Type::Float, Type::Bool, Type::String];
+
+    /// Returns the name of the type in source code (e.g. `int`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Bool => "bool",
+            Type::String => "string",
+        }
+    }
+
+    /// Tries to match a type name in source code to a `Type`
+    pub fn parse(name: &str) -> Option<Type> {
+        Type::ALL.into_iter().find(|ty| ty.as_str() == name)
+    }
+}
+
+/// Result types for a binary operator, indexed as `[lhs][rhs]`
+type Matrix = [[Option<Type>; 4]; 4];
+
+const I: Option<Type> = Some(Type::Int);
+const F: Option<Type> = Some(Type::Float);
+const B: Option<Type> = Some(Type::Bool);
+const S: Option<Type> = Some(Type::String);
+const X: Option<Type> = None;
+
+/// `+`: arithmetic, or concatenation of two strings (numbers are never converted to strings)
+static ADDITION: Matrix = [
+    //  Int Float Bool String
+    [I, F, X, X], // Int
+    [F, F, X, X], // Float
+    [X, X, X, X], // Bool
+    [X, X, X, S], // String
+];
+
+/// `- * / %`: numeric operands only, `Int` with `Float` promotes to `Float`
+static ARITHMETIC: Matrix = [
+    //  Int Float Bool String
+    [I, F, X, X], // Int
+    [F, F, X, X], // Float
+    [X, X, X, X], // Bool
+    [X, X, X, X], // String
+];
+
+/// `=`: the right operand must have the type of the left one, except `Int` widening into `Float`
+static ASSIGNMENT: Matrix = [
+    //  Int Float Bool String
+    [I, X, X, X], // Int
+    [F, F, X, X], // Float
+    [X, X, B, X], // Bool
+    [X, X, X, S], // String
+];
+
+/// `== !=`: any two numbers, two booleans, or two strings
+static EQUALITY: Matrix = [
+    //  Int Float Bool String
+    [B, B, X, X], // Int
+    [B, B, X, X], // Float
+    [X, X, B, X], // Bool
+    [X, X, X, B], // String
+];
+
+/// `< <= > >=`: any two numbers, or two strings, which compare lexicographically by character
+static ORDERING: Matrix = [
+    //  Int Float Bool String
+    [B, B, X, X], // Int
+    [B, B, X, X], // Float
+    [X, X, X, X], // Bool
+    [X, X, X, B], // String
+];
+
+/// `&& ||`: boolean operands only
+static LOGICAL: Matrix = [
+    //  Int Float Bool String
+    [X, X, X, X], // Int
+    [X, X, X, X], // Float
+    [X, X, B, X], // Bool
+    [X, X, X, X], // String
+];
+
+/// Operators that can never be used between two operands (e.g. `!`), whose result is not a
+/// primitive type (the `?` and `:` of a conditional, which has the type of its branches, and the
+/// ranges `..` and `..=`), or that are checked through another operator (compound assignments)
+static INVALID: Matrix = [[X; 4]; 4];
+
+/// Returns the cube row for an operator
+fn operator_matrix(op: Operator) -> &'static Matrix {
+    match op {
+        Operator::Plus => &ADDITION,
+        Operator::Minus | Operator::Multiply | Operator::Divide | Operator::Modulo => &ARITHMETIC,
+        Operator::Equal => &ASSIGNMENT,
+        Operator::EqualEqual | Operator::NotEqual => &EQUALITY,
+        Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => {
+            &ORDERING
+        }
+        Operator::And | Operator::Or => &LOGICAL,
+        Operator::Not
+        | Operator::Question
+        | Operator::Colon
+        | Operator::Range
+        | Operator::RangeInclusive
+        | Operator::PlusEqual
+        | Operator::MinusEqual
+        | Operator::MultiplyEqual
+        | Operator::DivideEqual => &INVALID,
+    }
+}
+
+/// Returns the type produced by `lhs op rhs`, or `None` if the combination is not allowed
+pub fn result_type(op: Operator, lhs: Type, rhs: Type) -> Option<Type> {
+    if let Some(base) = op.compound_base() {
+        let value = result_type(base, lhs, rhs)?;
+        return result_type(Operator::Equal, lhs, value);
+    }
+
+    operator_matrix(op)[lhs as usize][rhs as usize]
+}
+
+/// Returns the type produced by applying `op` as a prefix operator, or `None` if it is not allowed
+///
+/// Only `-` (on numbers) and `!` (on booleans) are unary operators.
+pub fn unary_result_type(op: Operator, operand: Type) -> Option<Type> {
+    match (op, operand) {
+        (Operator::Minus, Type::Int | Type::Float) => Some(operand),
+        (Operator::Not, Type::Bool) => Some(Type::Bool),
+        _ => None,
+    }
+}
+
+/// Returns the type produced by `value as target`, or `None` if the cast is not allowed
+///
+/// Numbers convert to either numeric type and booleans to `Int` (`true` is 1), while casting to
+/// the type a value already has does nothing. Converting `Float` to `Int` truncates toward zero.
+pub fn cast_type(value: Type, target: Type) -> Option<Type> {
+    match (value, target) {
+        _ if value == target => Some(target),
+        (Type::Int | Type::Float, Type::Int | Type::Float) | (Type::Bool, Type::Int) => {
+            Some(target)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,160 @@
+//! # Semantic Tokens
+//!
+//! Classifies the source for a language server, in the relative encoding of LSP semantic tokens.
+//! Token types are indices into `TOKEN_TYPES`:
+//!
+//! | Token                             | LSP token type |
+//! |-----------------------------------|----------------|
+//! | Keyword                           | `keyword`      |
+//! | Operator                          | `operator`     |
+//! | Integer, Decimal                  | `number`       |
+//! | Identifier                        | `variable`     |
+//! | Identifier right after `fn`       | `function`, with the `declaration` modifier |
+//! | StringLiteral, InterpolatedString | `string`       |
+//! | `//` and `/* */` comments         | `comment`      |
+//! | Punctuation                       | not emitted    |
+//!
+//! Tokens spanning several lines (strings and block comments) are split into one token per line,
+//! leaving out the line breaks, for clients without multi-line token support. Columns and lengths
+//! are counted in UTF-16 code units, the LSP default, and lines end at `\n` as everywhere else in
+//! this crate.
+
+use crate::line_index::LineIndex;
+use crate::{Keyword, LexError, Span, Token, comment_spans, extract_tokens_with_spans};
+
+/// Legend of token types, indexed by `SemanticToken::token_type`
+pub const TOKEN_TYPES: [&str; 7] = [
+    "keyword", "operator", "number", "variable", "function", "string", "comment",
+];
+
+/// Legend of token modifiers, indexed by the bits of `SemanticToken::modifiers`
+pub const TOKEN_MODIFIERS: [&str; 1] = ["declaration"];
+
+const KEYWORD: u32 = 0;
+const OPERATOR: u32 = 1;
+const NUMBER: u32 = 2;
+const VARIABLE: u32 = 3;
+const FUNCTION: u32 = 4;
+const STRING: u32 = 5;
+const COMMENT: u32 = 6;
+
+const DECLARATION: u32 = 1 << 0;
+
+/// A token relative to the one before it, as sent in an LSP `SemanticTokens` response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SemanticToken {
+    /// Lines since the previous token
+    pub delta_line: u32,
+    /// Columns since the start of the previous token if on the same line, or else since the
+    /// start of the line
+    pub delta_start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// A semantic token with its position decoded, with 0-based line and column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsoluteToken {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+/// Lexes the source and returns its semantic tokens
+pub fn semantic_tokens(source: &str) -> Result<Vec<SemanticToken>, LexError> {
+    let tokens = extract_tokens_with_spans(source)?;
+    let index = LineIndex::new(source);
+    let mut encoded = Vec::new();
+    let (mut previous_line, mut previous_start) = (0, 0);
+    let mut push = |span: Span, token_type: u32, modifiers: u32| {
+        for (line, start, length) in line_pieces(source, &index, span) {
+            encoded.push(SemanticToken {
+                delta_line: line - previous_line,
+                delta_start: if line == previous_line {
+                    start - previous_start
+                } else {
+                    start
+                },
+                length,
+                token_type,
+                modifiers,
+            });
+            (previous_line, previous_start) = (line, start);
+        }
+    };
+
+    let mut after_fn = false;
+    for (token, span) in &tokens {
+        let (token_type, modifiers) = match token {
+            Token::Whitespace => {
+                for comment in comment_spans(source, *span) {
+                    push(comment, COMMENT, 0);
+                }
+                continue;
+            }
+            Token::Keyword(_) => (KEYWORD, 0),
+            Token::Operator(_) => (OPERATOR, 0),
+            Token::Integer(_) | Token::Decimal(_) => (NUMBER, 0),
+            Token::Identifier(_) if after_fn => (FUNCTION, DECLARATION),
+            Token::Identifier(_) => (VARIABLE, 0),
+            Token::StringLiteral(_) | Token::InterpolatedString(_) => (STRING, 0),
+            Token::Punctuation(_) => {
+                after_fn = false;
+                continue;
+            }
+        };
+        after_fn = *token == Token::Keyword(Keyword::Fn);
+        push(*span, token_type, modifiers);
+    }
+
+    Ok(encoded)
+}
+
+/// Splits a span into the 0-based line, UTF-16 start column and UTF-16 length of its part on
+/// each line, skipping line breaks and empty parts
+fn line_pieces(source: &str, index: &LineIndex, span: Span) -> Vec<(u32, u32, u32)> {
+    let (first_line, _) = index.line_col(span.start);
+    let mut pieces = Vec::new();
+    let mut piece_start = span.start;
+
+    for (line, raw) in (first_line - 1..).zip(source[span.start..span.end].split('\n')) {
+        let text = raw.strip_suffix('\r').unwrap_or(raw);
+        if !text.is_empty() {
+            pieces.push((
+                line,
+                index.column_utf16(piece_start) - 1,
+                text.encode_utf16().count() as u32,
+            ));
+        }
+        piece_start += raw.len() + 1;
+    }
+
+    pieces
+}
+
+/// Turns relative semantic tokens back into absolute positions
+pub fn decode_semantic_tokens(tokens: &[SemanticToken]) -> Vec<AbsoluteToken> {
+    let (mut line, mut start) = (0, 0);
+
+    tokens
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                line += token.delta_line;
+                start = token.delta_start;
+            } else {
+                start += token.delta_start;
+            }
+            AbsoluteToken {
+                line,
+                start,
+                length: token.length,
+                token_type: token.token_type,
+                modifiers: token.modifiers,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,135 @@
+//! # Token Statistics
+//!
+//! Counts the tokens of a program for quick analysis of a corpus: how many tokens of each kind,
+//! how often each identifier, keyword and operator appears, and the range of its integer literals.
+
+use crate::{Keyword, LexError, Operator, Token, TokenKind, extract_tokens_with_spans};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Counts gathered from a sequence of tokens, excluding whitespace
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TokenStats {
+    /// Number of non-whitespace tokens
+    pub total: usize,
+    pub kinds: BTreeMap<TokenKind, usize>,
+    pub identifiers: BTreeMap<String, usize>,
+    pub keywords: BTreeMap<Keyword, usize>,
+    pub operators: BTreeMap<Operator, usize>,
+    pub integers: Option<IntegerStats>,
+    /// Number of lines with something other than whitespace, if the source was available
+    pub lines_of_code: Option<usize>,
+}
+
+/// Range and average of the integer literals in a program
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegerStats {
+    pub min: i64,
+    pub max: i64,
+    pub mean: f64,
+}
+
+impl TokenStats {
+    /// Returns the identifiers from most to least frequent, breaking ties alphabetically
+    pub fn identifiers_by_frequency(&self) -> Vec<(&str, usize)> {
+        let mut identifiers: Vec<(&str, usize)> = self
+            .identifiers
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect();
+        identifiers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        identifiers
+    }
+}
+
+/// Counts the tokens, skipping whitespace
+pub fn token_stats(tokens: &[Token]) -> TokenStats {
+    let mut stats = TokenStats::default();
+    let mut integers = Vec::new();
+
+    for token in tokens {
+        match token {
+            Token::Whitespace => continue,
+            Token::Identifier(name) => *stats.identifiers.entry(name.clone()).or_insert(0) += 1,
+            Token::Keyword(keyword) => *stats.keywords.entry(keyword.clone()).or_insert(0) += 1,
+            Token::Operator(op) => *stats.operators.entry(*op).or_insert(0) += 1,
+            Token::Integer(value) => integers.push(*value),
+            _ => {}
+        }
+        stats.total += 1;
+        *stats.kinds.entry(token.kind()).or_insert(0) += 1;
+    }
+
+    if let (Some(min), Some(max)) = (integers.iter().min(), integers.iter().max()) {
+        let sum: f64 = integers.iter().map(|&value| value as f64).sum();
+        stats.integers = Some(IntegerStats {
+            min: *min,
+            max: *max,
+            mean: sum / integers.len() as f64,
+        });
+    }
+
+    stats
+}
+
+/// Lexes the source and counts its tokens and lines of code
+pub fn source_stats(source: &str) -> Result<TokenStats, LexError> {
+    let tokens: Vec<Token> = extract_tokens_with_spans(source)?
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect();
+
+    let mut stats = token_stats(&tokens);
+    stats.lines_of_code = Some(lines_of_code(source));
+    Ok(stats)
+}
+
+/// Returns the number of lines with something other than whitespace
+pub fn lines_of_code(source: &str) -> usize {
+    source
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+impl fmt::Display for TokenStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Tokens: {}", self.total)?;
+        if let Some(lines) = self.lines_of_code {
+            writeln!(f, "Lines of code: {}", lines)?;
+        }
+        for (kind, count) in &self.kinds {
+            writeln!(f, "  {:?}: {}", kind, count)?;
+        }
+
+        let identifiers: Vec<String> = self
+            .identifiers_by_frequency()
+            .iter()
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect();
+        writeln!(f, "Identifiers: {}", identifiers.join(", "))?;
+
+        let keywords: Vec<String> = self
+            .keywords
+            .iter()
+            .map(|(keyword, count)| format!("{} ({})", keyword.as_str(), count))
+            .collect();
+        writeln!(f, "Keywords: {}", keywords.join(", "))?;
+
+        let operators: Vec<String> = self
+            .operators
+            .iter()
+            .map(|(op, count)| format!("{} ({})", op.as_str(), count))
+            .collect();
+        writeln!(f, "Operators: {}", operators.join(", "))?;
+
+        match &self.integers {
+            Some(integers) => writeln!(
+                f,
+                "Integers: min {}, max {}, mean {:.2}",
+                integers.min, integers.max, integers.mean
+            ),
+            None => writeln!(f, "Integers: none"),
+        }
+    }
+}
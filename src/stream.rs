@@ -0,0 +1,233 @@
+//! # Streaming
+//!
+//! Lexes input from a `BufRead` without loading all of it into memory. Input is read in chunks
+//! into a buffer, and a token is only returned once enough input after it has been read to be
+//! sure it cannot grow any longer. The rest of the buffer is kept and lexed again after the next
+//! chunk is read, so tokens split across chunks come out whole.
+
+use crate::{BorrowedToken, LexError, LexErrorKind, Lexer, Span, Token};
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+/// Number of bytes read at a time by `lex_reader`
+pub const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// Number of bytes after a token that can still change it
+///
+/// The lexer never needs to see more than two characters past a token to know it is complete: an
+/// integer only becomes a decimal after a `.` and a digit, whitespace only continues into a
+/// comment after `//` or `/*`, and an operator only grows one character at a time (`.` to `..` to
+/// `..=`). All of these are ASCII. The one exception is an `r` followed by any number of `#`s,
+/// which may start a raw string; `lex_buffer` holds it back separately.
+const LOOKAHEAD: usize = 2;
+
+/// Lexes the input read from `reader`, returning each token as soon as it is complete
+///
+/// Produces the same tokens as `extract_tokens`, including whitespace, and stops after the first
+/// error.
+pub fn lex_reader<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Token, LexError>> {
+    lex_reader_with_capacity(reader, DEFAULT_CAPACITY)
+}
+
+/// Lexes the input read from `reader`, reading at most `capacity` bytes at a time
+///
+/// # Panics
+/// Panics if `capacity` is zero.
+pub fn lex_reader_with_capacity<R: BufRead>(
+    reader: R,
+    capacity: usize,
+) -> impl Iterator<Item = Result<Token, LexError>> {
+    assert!(capacity > 0, "capacity must be positive");
+
+    TokenReader {
+        reader,
+        capacity,
+        buffer: String::new(),
+        partial: Vec::new(),
+        base: 0,
+        previous: None,
+        ready: VecDeque::new(),
+        read_error: None,
+        eof: false,
+        finished: false,
+    }
+}
+
+/// Iterator returned by `lex_reader`
+struct TokenReader<R> {
+    reader: R,
+    capacity: usize,
+    /// Text read but not lexed yet
+    buffer: String,
+    /// Bytes at the end of the input read so far that do not form a whole character yet
+    partial: Vec<u8>,
+    /// Offset in the whole input of the start of `buffer`
+    base: usize,
+    /// Last non-whitespace token, which ends right where `buffer` starts
+    previous: Option<(Token, Span)>,
+    /// Items lexed but not returned yet
+    ready: VecDeque<Result<Token, LexError>>,
+    /// Error reported once the input before it has been lexed
+    read_error: Option<LexError>,
+    eof: bool,
+    finished: bool,
+}
+
+impl<R: BufRead> Iterator for TokenReader<R> {
+    type Item = Result<Token, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.ready.pop_front() {
+                return Some(item);
+            }
+            if self.finished {
+                return None;
+            }
+
+            if !self.eof {
+                self.fill();
+            }
+            self.lex_buffer();
+        }
+    }
+}
+
+impl<R: BufRead> TokenReader<R> {
+    /// Reads the next chunk into the buffer, setting `eof` at the end of the input or on an error
+    fn fill(&mut self) {
+        let chunk = match self.reader.fill_buf() {
+            Ok(chunk) => &chunk[..chunk.len().min(self.capacity)],
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => return,
+            Err(error) => {
+                self.stop_reading(LexErrorKind::Io(error.kind()));
+                return;
+            }
+        };
+
+        if chunk.is_empty() {
+            if !self.partial.is_empty() {
+                self.stop_reading(LexErrorKind::InvalidUtf8);
+            }
+            self.eof = true;
+            return;
+        }
+
+        self.partial.extend_from_slice(chunk);
+        let consumed = chunk.len();
+        self.reader.consume(consumed);
+
+        // A character split across chunks stays in `partial` until the rest of it is read
+        let (valid, invalid) = match std::str::from_utf8(&self.partial) {
+            Ok(text) => (text.len(), false),
+            Err(error) => (error.valid_up_to(), error.error_len().is_some()),
+        };
+        let text = std::str::from_utf8(&self.partial[..valid]).expect("validated above");
+        self.buffer.push_str(text);
+        self.partial.drain(..valid);
+
+        if invalid {
+            self.stop_reading(LexErrorKind::InvalidUtf8);
+        }
+    }
+
+    /// Ends the input after the text read so far, reporting `kind` after its tokens
+    fn stop_reading(&mut self, kind: LexErrorKind) {
+        let start = self.base + self.buffer.len();
+        let end = match kind {
+            LexErrorKind::InvalidUtf8 => start + 1,
+            _ => start,
+        };
+
+        self.read_error = Some(LexError {
+            kind,
+            span: Span::new(start, end),
+        });
+        self.partial.clear();
+        self.eof = true;
+    }
+
+    /// Moves every token of the buffer that cannot change anymore to `ready`
+    fn lex_buffer(&mut self) {
+        let mut consumed = 0;
+
+        let mut lexer = Lexer::new(&self.buffer, true);
+        if self.base > 0 {
+            lexer = lexer.without_prefix();
+        }
+
+        let mut items = lexer.peekable();
+        while let Some(item) = items.next() {
+            let end = match &item {
+                Ok((_, span)) => span.end,
+                Err(error) => error.span.end,
+            };
+            // A token followed only by `#` could be the `r` of a raw string like `r##"..."##`
+            if !self.eof
+                && (end + LOOKAHEAD > self.buffer.len()
+                    || self.buffer[end..].bytes().all(|b| b == b'#'))
+            {
+                break;
+            }
+            // The lexer returns the whitespace before a block comment that is not closed yet on
+            // its own, but once the comment is read whole it is part of that whitespace
+            if !self.eof
+                && matches!(item, Ok((BorrowedToken::Whitespace, _)))
+                && matches!(
+                    items.peek(),
+                    Some(Err(LexError {
+                        kind: LexErrorKind::UnterminatedComment,
+                        ..
+                    }))
+                )
+            {
+                break;
+            }
+
+            let (token, span) = match item {
+                Ok((token, span)) => (token, span.shifted(self.base)),
+                Err(error) => {
+                    self.ready.push_back(Err(LexError {
+                        span: error.span.shifted(self.base),
+                        ..error
+                    }));
+                    self.finished = true;
+                    return;
+                }
+            };
+
+            // The lexer cannot see the token before the buffer, so check the separator here
+            if token != BorrowedToken::Whitespace
+                && span.start == self.base
+                && let Some((previous, prev_span)) = self.previous.take()
+                && crate::needs_separator(previous.kind(), token.kind())
+            {
+                self.ready.push_back(Err(LexError {
+                    kind: LexErrorKind::MissingSeparator {
+                        previous,
+                        next: token.to_owned(),
+                    },
+                    span: Span::new(prev_span.start, span.end),
+                }));
+                self.finished = true;
+                return;
+            }
+
+            let token = token.to_owned();
+            self.previous = match token {
+                Token::Whitespace => None,
+                _ => Some((token.clone(), span)),
+            };
+            self.ready.push_back(Ok(token));
+            consumed = span.end - self.base;
+        }
+
+        self.buffer.drain(..consumed);
+        self.base += consumed;
+
+        if self.eof {
+            self.ready.extend(self.read_error.take().map(Err));
+            self.finished = true;
+        }
+    }
+}
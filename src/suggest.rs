@@ -0,0 +1,125 @@
+//! # Keyword Suggestions
+//!
+//! Finds identifiers that are probably misspelled keywords (e.g. `whlie`), which otherwise lex
+//! without complaint and only cause confusing errors later on. An identifier is reported when its
+//! edit distance to a keyword, ignoring case, is within a threshold that grows with its length:
+//!
+//! | Identifier length | Maximum distance                            |
+//! |-------------------|---------------------------------------------|
+//! | 1–2               | none reported                               |
+//! | 3–5               | 1                                           |
+//! | 6 or more         | 2                                           |
+//!
+//! Swapping two adjacent letters counts as a single edit, so `whlie` is one edit away from `while`.
+//! Keywords shorter than 3 characters (`if`, `in`, `as`) are never suggested, as `int`, `min` and
+//! `was` are names, not typos. Common words one letter away from a keyword, such as `form`, are
+//! never reported either.
+//!
+//! An identifier right after `as` names a type (e.g. `x as int`), so it is never reported.
+
+use crate::diagnostic::Diagnostic;
+use crate::{KEYWORDS, Keyword, Span, Token};
+
+/// Diagnostic code of the warning about a misspelled keyword
+pub const MISSPELLED_KEYWORD: &str = "W0001";
+
+/// An identifier that looks like a misspelled keyword
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub span: Span,
+    pub identifier: String,
+    /// The keyword that was probably meant
+    pub keyword: &'static str,
+}
+
+impl Suggestion {
+    /// Returns the warning shown for this suggestion
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::warning(
+            format!(
+                "`{}` looks like a misspelling of the keyword `{}`",
+                self.identifier, self.keyword
+            ),
+            self.span,
+        )
+        .with_code(MISSPELLED_KEYWORD)
+    }
+}
+
+/// Returns a suggestion for each identifier close enough to a keyword
+pub fn suggest_keywords(tokens: &[(Token, Span)]) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    let mut after_as = false;
+
+    for (token, span) in tokens {
+        match token {
+            Token::Whitespace => continue,
+            Token::Identifier(name) if !after_as => {
+                if let Some(keyword) = closest_keyword(name) {
+                    suggestions.push(Suggestion {
+                        span: *span,
+                        identifier: name.clone(),
+                        keyword,
+                    });
+                }
+            }
+            _ => {}
+        }
+        after_as = *token == Token::Keyword(Keyword::As);
+    }
+
+    suggestions
+}
+
+/// Length of the shortest keywords that can be suggested
+const MIN_KEYWORD_LEN: usize = 3;
+
+/// Ordinary words within the threshold of a keyword, which are never reported
+const COMMON_WORDS: [&str; 4] = ["fork", "form", "fort", "four"];
+
+/// Returns the keyword closest to `name` if it is within the threshold for its length
+fn closest_keyword(name: &str) -> Option<&'static str> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let max_distance = match name.len() {
+        0..=2 => return None,
+        3..=5 => 1,
+        _ => 2,
+    };
+    if COMMON_WORDS.contains(&name.iter().collect::<String>().as_str()) {
+        return None;
+    }
+
+    KEYWORDS
+        .keys()
+        .filter(|keyword| keyword.chars().count() >= MIN_KEYWORD_LEN)
+        .map(|keyword| (edit_distance(&name, keyword), *keyword))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min()
+        .map(|(_, keyword)| keyword)
+}
+
+/// Returns the number of insertions, deletions, substitutions and swaps of adjacent characters
+/// needed to turn `a` into `b` (optimal string alignment distance)
+fn edit_distance(a: &[char], b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // d[i][j] is the distance between a[..i] and b[..j]
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,99 @@
+//! # Token Table
+//!
+//! Formats tokens as an aligned table for reviewing a whole file, one row per token:
+//!
+//! ```text
+//! 0 1:1 Keyword    while
+//! 1 1:7 Identifier x
+//! 2 1:9 Operator   <=
+//! ```
+//!
+//! Each row has the index of the token (zero-padded), the line and column where it starts, its
+//! kind and its lexeme. Lexemes are the exact text of the source covered by the token, with
+//! control characters such as line breaks escaped (`\n`) so each row stays on one line. Lexemes
+//! longer than `TableOptions::max_lexeme_width` characters are cut short with `…`.
+//!
+//! Every column is as wide as its widest entry, so the table lines up for any input.
+
+use crate::line_index::LineIndex;
+use crate::{Span, Token, TokenKind, color};
+
+/// Settings for `format_table`
+#[derive(Debug, Clone)]
+pub struct TableOptions {
+    /// Whether `Token::Whitespace` gets a row along with the other tokens
+    pub include_whitespace: bool,
+    /// Number of characters a lexeme is cut down to, counting the `…` that replaces the rest
+    pub max_lexeme_width: usize,
+    /// Whether lexemes are colored by the kind of their token (see `color`)
+    pub color: bool,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            include_whitespace: false,
+            max_lexeme_width: 40,
+            color: false,
+        }
+    }
+}
+
+/// Formats the tokens of `source` as a table, with a line per row
+pub fn format_table(source: &str, tokens: &[(Token, Span)], options: &TableOptions) -> String {
+    let index = LineIndex::new(source);
+    let rows: Vec<(String, String, String, TokenKind)> = tokens
+        .iter()
+        .filter(|(token, _)| options.include_whitespace || *token != Token::Whitespace)
+        .map(|(token, span)| {
+            let (line, column) = index.line_col(span.start);
+            let lexeme = source.get(span.start..span.end).unwrap_or("");
+            (
+                format!("{}:{}", line, column),
+                format!("{:?}", token.kind()),
+                truncate(&escape(lexeme), options.max_lexeme_width.max(1)),
+                token.kind(),
+            )
+        })
+        .collect();
+
+    let index_width = rows.len().saturating_sub(1).to_string().len();
+    let position_width = rows.iter().map(|row| row.0.len()).max().unwrap_or(0);
+    let kind_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for (i, (position, kind, lexeme, token_kind)) in rows.iter().enumerate() {
+        let style = options
+            .color
+            .then(|| color::token_style(*token_kind))
+            .flatten();
+        out += &format!(
+            "{:0index_width$} {:<position_width$} {:<kind_width$} {}\n",
+            i,
+            position,
+            kind,
+            color::paint(lexeme, style)
+        );
+    }
+
+    out
+}
+
+/// Replaces control characters with their escape sequences (e.g. `\n`)
+fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            c if c.is_control() => c.escape_default().to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Cuts text down to `width` characters, ending with `…` if anything was cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    text.chars().take(width - 1).chain(['…']).collect()
+}
@@ -0,0 +1,90 @@
+//! # Snapshot Testing
+//!
+//! Helpers for golden-file tests, available with the `testing` feature. A source file is lexed and
+//! its tokens are written one per line, then compared against a checked-in `.tokens` file:
+//!
+//! ```text
+//! Keyword "while" 1:1
+//! Identifier "x" 1:7
+//! error: Unrecognized token starting at position 9: '$'
+//! ```
+//!
+//! Run the tests with `UPDATE_SNAPSHOTS=1` to write the current output to the `.tokens` files
+//! instead of comparing against them.
+
+use crate::line_index::LineIndex;
+use crate::{LexerOptions, extract_file_contents, extract_tokens_with_options};
+use std::fs;
+use std::path::Path;
+
+/// Environment variable that makes `check_snapshot` regenerate the expected files
+pub const UPDATE_VAR: &str = "UPDATE_SNAPSHOTS";
+
+/// Formats each non-whitespace token as `kind "lexeme" line:col`, followed by an `error:` line if
+/// the source cannot be lexed
+pub fn snapshot(source: &str) -> String {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+    let mut out = String::new();
+
+    let (tokens, error) = match extract_tokens_with_options(source, &options) {
+        Ok(tokens) => (tokens, None),
+        Err(error) => {
+            // The tokens before the error are still worth recording
+            let tokens = crate::Lexer::new(source, false)
+                .map_while(Result::ok)
+                .map(|(token, span)| (token.to_owned(), span))
+                .collect();
+            (tokens, Some(error))
+        }
+    };
+
+    let index = LineIndex::new(source);
+    for (token, span) in tokens {
+        let (line, column) = index.line_col(span.start);
+        out += &format!(
+            "{:?} {:?} {}:{}\n",
+            token.kind(),
+            &source[span.start..span.end],
+            line,
+            column
+        );
+    }
+    if let Some(error) = error {
+        out += &format!("error: {}\n", error);
+    }
+
+    out
+}
+
+/// Lexes the file at `input_path` and compares its snapshot against the file at `expected_path`
+///
+/// With `UPDATE_SNAPSHOTS` set to `1`, the expected file is overwritten instead.
+///
+/// # Panics
+/// Panics if the snapshots differ, or if a file cannot be read or written.
+pub fn check_snapshot(input_path: &Path, expected_path: &Path) {
+    let actual = snapshot(&extract_file_contents(&input_path.to_string_lossy()));
+
+    if std::env::var(UPDATE_VAR).is_ok_and(|value| value == "1") {
+        fs::write(expected_path, actual).expect("Failed to write snapshot");
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(expected_path) else {
+        panic!(
+            "Missing snapshot {}; run with {}=1 to create it",
+            expected_path.display(),
+            UPDATE_VAR
+        );
+    };
+    if actual != expected {
+        panic!(
+            "Snapshot mismatch for {}\n--- expected\n{}--- actual\n{}",
+            input_path.display(),
+            expected,
+            actual
+        );
+    }
+}
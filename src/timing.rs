@@ -0,0 +1,124 @@
+//! # Phase Timings
+//!
+//! Records how long each phase of processing a file took and how much it produced, so the
+//! expensive phases of a large input can be found. Each phase is timed once as a whole rather than
+//! per token, and the driver only records timings when asked to (`--time`).
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A step of processing a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Reading the file, measured in bytes
+    Read,
+    /// Splitting the source into tokens, measured in tokens
+    Lex,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Read => "read",
+            Phase::Lex => "lex",
+        }
+    }
+
+    /// Returns what the size of the phase counts
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Phase::Read => "bytes",
+            Phase::Lex => "tokens",
+        }
+    }
+}
+
+/// How long a phase took and how much it produced
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub duration: Duration,
+    /// Amount produced, in the unit given by `Phase::unit`
+    pub size: usize,
+}
+
+/// Timings of the phases run so far, in the order they ran
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseTimings {
+    phases: Vec<PhaseTiming>,
+}
+
+impl PhaseTimings {
+    pub fn new() -> Self {
+        PhaseTimings::default()
+    }
+
+    /// Runs a phase, recording its duration and the size `size` gives its result
+    pub fn time<T>(
+        &mut self,
+        phase: Phase,
+        run: impl FnOnce() -> T,
+        size: impl Fn(&T) -> usize,
+    ) -> T {
+        let start = Instant::now();
+        let result = run();
+        let duration = start.elapsed();
+
+        self.phases.push(PhaseTiming {
+            phase,
+            duration,
+            size: size(&result),
+        });
+        result
+    }
+
+    /// Returns the timings in the order the phases ran
+    pub fn phases(&self) -> &[PhaseTiming] {
+        &self.phases
+    }
+
+    /// Returns the timing of a phase, or `None` if it has not run
+    pub fn get(&self, phase: Phase) -> Option<&PhaseTiming> {
+        self.phases.iter().find(|timing| timing.phase == phase)
+    }
+
+    /// Returns the time taken by every phase together
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|timing| timing.duration).sum()
+    }
+}
+
+/// Runs a phase, recording it in `timings` if given and otherwise just running it
+pub fn time_phase<T>(
+    timings: Option<&mut PhaseTimings>,
+    phase: Phase,
+    run: impl FnOnce() -> T,
+    size: impl Fn(&T) -> usize,
+) -> T {
+    match timings {
+        Some(timings) => timings.time(phase, run, size),
+        None => run(),
+    }
+}
+
+/// Formats the timings as a table with one row per phase and a total
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<8} {:>12} {:>12}", "phase", "time (ms)", "size")?;
+        for timing in &self.phases {
+            writeln!(
+                f,
+                "{:<8} {:>12.3} {:>12} {}",
+                timing.phase.as_str(),
+                milliseconds(timing.duration),
+                timing.size,
+                timing.phase.unit()
+            )?;
+        }
+        writeln!(f, "{:<8} {:>12.3}", "total", milliseconds(self.total()))
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
@@ -0,0 +1,58 @@
+//! # WebAssembly Bindings
+//!
+//! Functions exported to JavaScript with `wasm-bindgen`, available with the `wasm` feature. They
+//! only use the lexing functions that return errors instead of panicking, so no panic can cross
+//! into JavaScript, and they return strings that are easy to use from a web page:
+//!
+//! ```text
+//! lex_to_json("x = 1")
+//! {"tokens": [{"kind": "Identifier", "text": "x", "start": 0, "end": 1}, ...]}
+//!
+//! lex_to_json("x = $")
+//! {"error": {"code": "L0001", "message": "Unrecognized token ...", "start": 4, "end": 5}}
+//! ```
+//!
+//! Spans are byte offsets into the UTF-8 encoding of the source.
+
+use crate::{LexerOptions, extract_tokens_with_options, highlight as html, json};
+use wasm_bindgen::prelude::*;
+
+/// Lexes the source and returns its non-whitespace tokens, or the error that stopped the lexer,
+/// as JSON
+#[wasm_bindgen]
+pub fn lex_to_json(source: &str) -> String {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    match extract_tokens_with_options(source, &options) {
+        Ok(tokens) => {
+            let tokens: Vec<String> = tokens
+                .iter()
+                .map(|(token, span)| {
+                    format!(
+                        "{{\"kind\": {}, \"text\": {}, \"start\": {}, \"end\": {}}}",
+                        json::string(&format!("{:?}", token.kind())),
+                        json::string(&source[span.start..span.end]),
+                        span.start,
+                        span.end
+                    )
+                })
+                .collect();
+            format!("{{\"tokens\": [{}]}}", tokens.join(", "))
+        }
+        Err(error) => format!(
+            "{{\"error\": {{\"code\": {}, \"message\": {}, \"start\": {}, \"end\": {}}}}}",
+            json::string(error.kind.code()),
+            json::string(&error.to_string()),
+            error.span.start,
+            error.span.end
+        ),
+    }
+}
+
+/// Returns the source as highlighted HTML, or just escaped if it cannot be lexed
+#[wasm_bindgen]
+pub fn highlight(source: &str) -> String {
+    html::highlight_html(source).unwrap_or_else(|_| html::escape_html(source))
+}
@@ -0,0 +1,205 @@
+use compiler_project_tc3002_b::batch::*;
+use compiler_project_tc3002_b::{LexError, LexErrorKind, TokenKind};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Creates a fresh temporary directory with a mix of valid and invalid input files
+fn setup_directory(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lexer_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+
+    fs::write(dir.join("a_valid.txt"), "while x <= 10 x += 1").unwrap();
+    fs::write(dir.join("nested/b_valid.lang"), "fn f 3.5").unwrap();
+    fs::write(dir.join("c_lex_error.txt"), "x = 1 $").unwrap();
+    fs::write(dir.join("d_invalid_utf8.txt"), [b'x', 0xff, 0xfe]).unwrap();
+    fs::write(dir.join("ignored.md"), "$$$").unwrap();
+
+    dir
+}
+
+fn file_names(reports: &[FileReport], dir: &Path) -> Vec<String> {
+    reports
+        .iter()
+        .map(|r| r.path.strip_prefix(dir).unwrap().display().to_string())
+        .collect()
+}
+
+#[test]
+fn test_run_many_collects_directory_recursively() {
+    let dir = setup_directory("batch_collect");
+    let reports = run_many(std::slice::from_ref(&dir));
+
+    assert_eq!(
+        file_names(&reports, &dir),
+        vec![
+            "a_valid.txt",
+            "c_lex_error.txt",
+            "d_invalid_utf8.txt",
+            "nested/b_valid.lang"
+        ]
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_run_many_reports_each_file() {
+    let dir = setup_directory("batch_reports");
+    let reports = run_many(std::slice::from_ref(&dir));
+
+    let valid = &reports[0];
+    assert!(valid.is_ok());
+    assert_eq!(valid.token_count(), 7);
+    assert_eq!(valid.token_counts[&TokenKind::Keyword], 1);
+    assert_eq!(valid.token_counts[&TokenKind::Identifier], 2);
+    assert_eq!(valid.token_counts[&TokenKind::Operator], 2);
+    assert_eq!(valid.token_counts[&TokenKind::Integer], 2);
+
+    assert!(matches!(reports[1].errors.as_slice(), [FileError::Lex(_)]));
+    assert!(matches!(
+        reports[2].errors.as_slice(),
+        [FileError::Lex(LexError {
+            kind: LexErrorKind::InvalidUtf8,
+            span,
+        })] if span.start == 1
+    ));
+    assert!(reports[3].is_ok());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_run_many_with_custom_extensions() {
+    let dir = setup_directory("batch_extensions");
+    let options = BatchOptions {
+        extensions: vec!["lang".to_string()],
+        ..BatchOptions::default()
+    };
+    let reports = run_many_with_options(std::slice::from_ref(&dir), &options);
+
+    assert_eq!(file_names(&reports, &dir), vec!["nested/b_valid.lang"]);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Summarizes a report as comparable data: path, token counts and error messages
+fn report_summary(report: &FileReport) -> (PathBuf, Vec<(TokenKind, usize)>, Vec<String>) {
+    (
+        report.path.clone(),
+        report.token_counts.clone().into_iter().collect(),
+        report.errors.iter().map(|e| e.to_string()).collect(),
+    )
+}
+
+#[test]
+fn test_parallel_and_serial_reports_are_identical() {
+    let dir = setup_directory("batch_parallel");
+    for i in 0..20 {
+        fs::write(dir.join(format!("extra_{:02}.txt", i)), "x = ".repeat(i)).unwrap();
+    }
+
+    let serial = run_many_with_options(
+        std::slice::from_ref(&dir),
+        &BatchOptions {
+            threads: 1,
+            ..BatchOptions::default()
+        },
+    );
+    let parallel = run_many_with_options(
+        std::slice::from_ref(&dir),
+        &BatchOptions {
+            threads: 4,
+            ..BatchOptions::default()
+        },
+    );
+
+    assert_eq!(serial.len(), 24);
+    assert_eq!(
+        serial.iter().map(report_summary).collect::<Vec<_>>(),
+        parallel.iter().map(report_summary).collect::<Vec<_>>()
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_overflow_in_one_file_is_reported_as_error() {
+    let dir = setup_directory("batch_overflow");
+    // Does not fit in an i64
+    fs::write(dir.join("b_overflow.txt"), "x = 99999999999999999999").unwrap();
+
+    let reports = run_many(std::slice::from_ref(&dir));
+
+    assert_eq!(reports.len(), 5);
+    assert!(matches!(
+        reports[1].errors.as_slice(),
+        [FileError::Lex(LexError {
+            kind: LexErrorKind::IntegerOverflow,
+            ..
+        })]
+    ));
+    assert!(reports[0].is_ok());
+    assert!(reports[4].is_ok());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_exits_with_failure_when_a_file_fails() {
+    let dir = setup_directory("batch_cli");
+    let output = Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg(dir.join("a_valid.txt"))
+        .arg(dir.join("c_lex_error.txt"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(stdout.lines().nth(1).unwrap().ends_with(" ok"));
+    assert!(stdout.contains("error: Unrecognized token starting at position 6"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Returns the counts in the table row of the file named `name`, in column order
+fn table_row(table: &str, name: &str) -> Vec<usize> {
+    let row = table.lines().find(|line| line.contains(name)).unwrap();
+    row.split_whitespace()
+        .skip(1)
+        .map_while(|cell| cell.parse().ok())
+        .collect()
+}
+
+#[test]
+fn test_summary_table_counts_every_token() {
+    let dir = std::env::temp_dir().join(format!("lexer_batch_table_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("list.txt"),
+        "xs = [1, 2.5, \"a\", \"${xs}\"]\nwhile xs[0] < 3",
+    )
+    .unwrap();
+
+    let reports = run_many(std::slice::from_ref(&dir));
+    let table = summary_table(&reports);
+
+    let header = table.lines().next().unwrap();
+    assert!(header.contains("InterpolatedString"));
+    assert!(header.contains("Punctuation"));
+    assert_eq!(
+        table.lines().nth(1).unwrap().len(),
+        header.len() - " Status".len() + " ok".len()
+    );
+    let counts = table_row(&table, "list.txt");
+    assert_eq!(counts.iter().sum::<usize>(), reports[0].token_count());
+    assert_eq!(
+        counts.last(),
+        Some(&reports[0].token_counts[&TokenKind::Punctuation])
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,140 @@
+use compiler_project_tc3002_b::cache::*;
+use compiler_project_tc3002_b::*;
+use std::fs;
+use std::path::PathBuf;
+
+const SOURCE: &str = "\
+fn área x
+    total = x * 0.1 + -9223372036854775808
+    s = r\"C:\\new\" \"${x + 1} é\"
+    while ! done && x >= 2.5
+";
+
+/// Returns a path in the temporary directory unique to this test
+fn cache_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("lexer_cache_{}_{}.bin", name, std::process::id()))
+}
+
+fn source_tokens(source: &str) -> Vec<Token> {
+    extract_tokens_with_spans(source)
+        .unwrap()
+        .into_iter()
+        .map(|(token, _)| token)
+        .collect()
+}
+
+#[test]
+fn test_cache_round_trip() {
+    let path = cache_path("round_trip");
+    let mut tokens = source_tokens(SOURCE);
+    // Decimals keep the text they were written as, not just their value
+    tokens.push(Token::Decimal(DecimalLit::new("0.10").unwrap()));
+    tokens.push(Token::Decimal(
+        DecimalLit::new("0.30000000000000004").unwrap(),
+    ));
+
+    write_token_cache(&path, SOURCE, &tokens).unwrap();
+    let cached = read_token_cache(&path, SOURCE).unwrap().unwrap();
+
+    assert_eq!(cached, tokens);
+    assert!(
+        cached
+            .iter()
+            .any(|token| matches!(token, Token::InterpolatedString(_)))
+    );
+    assert!(cached.contains(&Token::Identifier("área".to_string())));
+    assert!(cached.contains(&Token::Integer(i64::MIN)));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_rejects_changed_source() {
+    let path = cache_path("changed");
+    let source = "x = 1";
+    write_token_cache(&path, source, &source_tokens(source)).unwrap();
+
+    assert_eq!(read_token_cache(&path, "x = 2").unwrap(), None);
+    assert_eq!(read_token_cache(&path, "x = 1 ").unwrap(), None);
+    assert!(read_token_cache(&path, source).unwrap().is_some());
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_rejects_other_version() {
+    let path = cache_path("version");
+    let source = "x = 1";
+    write_token_cache(&path, source, &source_tokens(source)).unwrap();
+
+    let mut bytes = fs::read(&path).unwrap();
+    assert_eq!(bytes[..4], MAGIC);
+    bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+
+    assert_eq!(read_token_cache(&path, source).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_deepest_interpolation() {
+    let path = cache_path("deepest");
+    let source = format!(
+        "x = {}1{}",
+        "\"${".repeat(MAX_INTERPOLATION_DEPTH),
+        "}\"".repeat(MAX_INTERPOLATION_DEPTH)
+    );
+    let tokens = source_tokens(&source);
+    write_token_cache(&path, &source, &tokens).unwrap();
+
+    assert_eq!(read_token_cache(&path, &source).unwrap(), Some(tokens));
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_nested_too_deep() {
+    let path = cache_path("too_deep");
+    let source = "x";
+    write_token_cache(&path, source, &[]).unwrap();
+    let mut bytes = fs::read(&path).unwrap();
+    // Keep the header and replace the empty token list with one interpolated string (tag 7)
+    // holding a single tokens part (tag 1) with another such string, many times over
+    let header_len = bytes.len() - 4;
+    bytes.truncate(header_len);
+    let levels = 200_000;
+    for _ in 0..levels {
+        bytes.extend(1u32.to_le_bytes());
+        bytes.push(7);
+        bytes.extend(1u32.to_le_bytes());
+        bytes.push(1);
+    }
+    bytes.extend(0u32.to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+
+    assert_eq!(read_token_cache(&path, source).unwrap(), None);
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cache_truncated_or_damaged_file() {
+    let path = cache_path("truncated");
+    let source = "while x\n  s = \"${x}\"";
+    write_token_cache(&path, source, &source_tokens(source)).unwrap();
+    let bytes = fs::read(&path).unwrap();
+
+    for len in 0..bytes.len() {
+        fs::write(&path, &bytes[..len]).unwrap();
+        assert_eq!(
+            read_token_cache(&path, source).unwrap(),
+            None,
+            "length {}",
+            len
+        );
+    }
+
+    let mut extended = bytes.clone();
+    extended.push(0);
+    fs::write(&path, extended).unwrap();
+    assert_eq!(read_token_cache(&path, source).unwrap(), None);
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(read_token_cache(&path, source).unwrap(), None);
+}
@@ -0,0 +1,198 @@
+use compiler_project_tc3002_b::check::*;
+use compiler_project_tc3002_b::diagnostic::Severity;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Creates a fresh temporary directory with a clean file, a file with a warning and a broken file
+fn setup_directory(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lexer_check_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("nested")).unwrap();
+
+    fs::write(dir.join("clean.lang"), "while x <= 10 x += 1\n").unwrap();
+    fs::write(dir.join("nested/warning.lang"), "whlie x\n").unwrap();
+    fs::write(dir.join("broken.lang"), "x = 1 $\ny = 2 @\n").unwrap();
+    fs::write(dir.join("ignored.md"), "$$$").unwrap();
+
+    dir
+}
+
+fn run_check(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg("check")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_check_source_collects_every_problem() {
+    let diagnostics = check_source("whlie x $\ny = 2 @\n");
+
+    let found: Vec<(Severity, Option<&str>)> = diagnostics
+        .iter()
+        .map(|diag| (diag.severity, diag.code))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Severity::Warning, Some("W0001")),
+            (Severity::Error, Some("L0001")),
+            (Severity::Error, Some("L0001")),
+        ]
+    );
+    assert!(check_source("x = 1\n").is_empty());
+}
+
+#[test]
+fn test_check_report_counts() {
+    let dir = setup_directory("counts");
+
+    let broken = check(&dir.join("broken.lang")).unwrap();
+    let warning = check(&dir.join("nested/warning.lang")).unwrap();
+    let clean = check(&dir.join("clean.lang")).unwrap();
+
+    assert_eq!((broken.error_count(), broken.warning_count()), (2, 0));
+    assert_eq!((warning.error_count(), warning.warning_count()), (0, 1));
+    assert!(!broken.is_ok());
+    assert!(warning.is_ok() && clean.is_ok());
+    assert_eq!(
+        summary(&[broken, warning, clean], 0),
+        "3 files checked, 2 errors, 1 warning"
+    );
+    assert_eq!(summary(&[], 1), "1 file checked, 1 error, 0 warnings");
+    assert!(check(&dir.join("missing.lang")).is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_check_clean_file() {
+    let dir = setup_directory("cli_clean");
+
+    let output = run_check(&[dir.join("clean.lang").to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "1 file checked, 0 errors, 0 warnings\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_check_directory() {
+    let dir = setup_directory("cli_directory");
+
+    let output = run_check(&[dir.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let broken = dir.join("broken.lang");
+    assert!(stderr.contains(&format!("{}:1:7", broken.display())));
+    assert!(stderr.contains(&format!("{}:2:7", broken.display())));
+    assert!(stderr.contains("warning: `whlie` looks like a misspelling"));
+    assert!(stderr.ends_with("3 files checked, 2 errors, 1 warning\n"));
+
+    let output = run_check(&["--error-format", "json", broken.to_str().unwrap()]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stderr
+            .lines()
+            .filter(|line| line.contains("\"code\": \"L0001\""))
+            .count(),
+        2
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_check_missing_file() {
+    let output = run_check(&["does/not/exist.lang"]);
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .ends_with("1 file checked, 1 error, 0 warnings\n")
+    );
+    assert_eq!(run_check(&[]).status.code(), Some(2));
+}
+
+#[test]
+fn test_cli_check_deny_warnings() {
+    let dir = setup_directory("cli_deny_warnings");
+    let warning = dir.join("nested/warning.lang");
+    let clean = dir.join("clean.lang");
+
+    assert_eq!(
+        run_check(&[warning.to_str().unwrap()]).status.code(),
+        Some(0)
+    );
+    let output = run_check(&["--deny-warnings", warning.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .ends_with("1 file checked, 0 errors, 1 warning\n")
+    );
+    assert_eq!(
+        run_check(&["--deny-warnings", clean.to_str().unwrap()])
+            .status
+            .code(),
+        Some(0)
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_check_deny_warnings_common_names() {
+    let dir = setup_directory("cli_common_names");
+    let path = dir.join("names.lang");
+    fs::write(
+        &path,
+        "form = 1
+min = form
+int = min
+has = int
+was = has
+",
+    )
+    .unwrap();
+
+    let output = run_check(&["--deny-warnings", path.to_str().unwrap()]);
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "1 file checked, 0 errors, 0 warnings\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_check_color() {
+    let dir = setup_directory("cli_color");
+    let broken = dir.join("broken.lang");
+
+    let colored = run_check(&["--color=always", broken.to_str().unwrap()]);
+    assert_eq!(colored.status.code(), Some(1));
+    assert!(
+        String::from_utf8(colored.stderr)
+            .unwrap()
+            .contains("\x1b[31m")
+    );
+
+    let plain = run_check(&["--color=never", broken.to_str().unwrap()]);
+    assert!(!String::from_utf8(plain.stderr).unwrap().contains('\x1b'));
+    assert_eq!(
+        run_check(&["--color=sometimes", broken.to_str().unwrap()])
+            .status
+            .code(),
+        Some(2)
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
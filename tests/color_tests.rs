@@ -0,0 +1,89 @@
+use compiler_project_tc3002_b::color::*;
+use compiler_project_tc3002_b::diagnostic::{Diagnostic, RenderOptions, render_with_options};
+use compiler_project_tc3002_b::table::{TableOptions, format_table};
+use compiler_project_tc3002_b::{
+    PrintOptions, extract_tokens, extract_tokens_with_spans, print_tokens,
+};
+
+const SOURCE: &str = "while x <= 2.5 print \"hi\"";
+
+fn print(color: bool) -> String {
+    let tokens = extract_tokens(SOURCE.to_string());
+    let mut out = Vec::new();
+    let options = PrintOptions {
+        color,
+        ..PrintOptions::default()
+    };
+    print_tokens(&tokens, &mut out, &options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn table(color: bool) -> String {
+    let tokens = extract_tokens_with_spans(SOURCE).unwrap();
+    let options = TableOptions {
+        color,
+        ..TableOptions::default()
+    };
+    format_table(SOURCE, &tokens, &options)
+}
+
+#[test]
+fn test_print_tokens_colored() {
+    let expected = "\
+Token: \x1b[34mKeyword(While)\x1b[0m
+Token: Identifier(\"x\")
+Token: \x1b[35mOperator(LessEqual)\x1b[0m
+Token: \x1b[33mDecimal(2.5)\x1b[0m
+Token: Identifier(\"print\")
+Token: \x1b[32mStringLiteral(\"hi\")\x1b[0m
+";
+    assert_eq!(print(true), expected);
+}
+
+#[test]
+fn test_table_colored() {
+    let colored = table(true);
+
+    assert!(colored.contains("Keyword       \x1b[34mwhile\x1b[0m\n"));
+    assert!(colored.contains("Identifier    x\n"));
+    assert!(colored.contains("Operator      \x1b[35m<=\x1b[0m\n"));
+    assert!(colored.contains("Decimal       \x1b[33m2.5\x1b[0m\n"));
+}
+
+#[test]
+fn test_never_colored() {
+    assert!(!print(false).contains('\x1b'));
+    assert!(!table(false).contains('\x1b'));
+}
+
+#[test]
+fn test_diagnostic_colored() {
+    let source = "x = $";
+    let error = extract_tokens_with_spans(source).unwrap_err();
+    let options = RenderOptions {
+        color: true,
+        ..RenderOptions::default()
+    };
+    let rendered = render_with_options(&Diagnostic::from(error), source, &options);
+
+    assert!(rendered.starts_with("\x1b[31merror\x1b[0m: "));
+    assert!(rendered.contains("    \x1b[31m^\x1b[0m\n"));
+    let plain = render_with_options(
+        &Diagnostic::from(extract_tokens_with_spans(source).unwrap_err()),
+        source,
+        &RenderOptions::default(),
+    );
+    assert!(!plain.contains('\x1b'));
+}
+
+#[test]
+fn test_color_choice() {
+    assert_eq!(ColorChoice::parse("always"), Some(ColorChoice::Always));
+    assert_eq!(ColorChoice::parse("yes"), None);
+
+    assert!(ColorChoice::Auto.enabled(true, false));
+    assert!(!ColorChoice::Auto.enabled(false, false));
+    assert!(!ColorChoice::Auto.enabled(true, true));
+    assert!(ColorChoice::Always.enabled(false, true));
+    assert!(!ColorChoice::Never.enabled(true, false));
+}
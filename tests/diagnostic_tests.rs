@@ -0,0 +1,341 @@
+use compiler_project_tc3002_b::diagnostic::*;
+use compiler_project_tc3002_b::*;
+
+fn render_lex_error(source: &str) -> String {
+    let error = extract_tokens_with_spans(source).unwrap_err();
+    render(&Diagnostic::from(error), source)
+}
+
+#[test]
+fn test_render_error_in_middle_of_line() {
+    let expected = "\
+error: Unrecognized token starting at position 6: '$'
+ --> 1:7
+  |
+1 | print $x + 3
+  |       ^
+";
+    assert_eq!(render_lex_error("print $x + 3"), expected);
+}
+
+#[test]
+fn test_render_error_at_end_of_line() {
+    let expected = "\
+error: Unrecognized token starting at position 12: '$'
+ --> 2:7
+  |
+2 | y = 2 $
+  |       ^
+";
+    assert_eq!(render_lex_error("x = 1\ny = 2 $\nz = 3"), expected);
+}
+
+#[test]
+fn test_render_error_spanning_multiple_characters() {
+    let expected = "\
+error: Missing separator between tokens Integer(42) and Operator(Plus)
+ --> 1:5
+  |
+1 | x = 42+3
+  |     ^^^
+";
+    assert_eq!(render_lex_error("x = 42+3"), expected);
+}
+
+#[test]
+fn test_render_expands_tabs_before_error() {
+    let expected = "\
+error: Unrecognized token starting at position 8: '$'
+ --> 1:9
+  |
+1 | if  x == $
+  |          ^
+";
+    assert_eq!(render_lex_error("if\tx == $"), expected);
+}
+
+#[test]
+fn test_render_leading_tab_with_tab_width() {
+    let source = "\tx = $";
+    let error = extract_tokens_with_spans(source).unwrap_err();
+    let options = RenderOptions {
+        tab_width: 8,
+        ..RenderOptions::default()
+    };
+    let expected = "\
+error: Unrecognized token starting at position 5: '$'
+ --> 1:6
+  |
+1 |         x = $
+  |             ^
+";
+    assert_eq!(
+        render_with_options(&Diagnostic::from(error), source, &options),
+        expected
+    );
+}
+
+#[test]
+fn test_render_in_file_with_tab_width() {
+    let source = "\tx = $";
+    let error = extract_tokens_with_spans(source).unwrap_err();
+    let options = RenderOptions {
+        tab_width: 2,
+        ..RenderOptions::default()
+    };
+    let expected = "\
+error: Unrecognized token starting at position 5: '$'
+ --> main.lang:1:6
+  |
+1 |   x = $
+  |       ^
+";
+    assert_eq!(
+        render_in_file(&Diagnostic::from(error), "main.lang", source, &options),
+        expected
+    );
+}
+
+#[test]
+fn test_render_counts_characters_not_bytes() {
+    let expected = "\
+error: Unrecognized token starting at position 7: '$'
+ --> 1:7
+  |
+1 | á = 1 $
+  |       ^
+";
+    assert_eq!(render_lex_error("á = 1 $"), expected);
+}
+
+#[test]
+fn test_render_warning_with_note() {
+    let diag = Diagnostic::warning("unused variable", Span::new(0, 5))
+        .with_note("prefix it with `_` to silence this warning");
+    let expected = "\
+warning: unused variable
+ --> 1:1
+  |
+1 | total = 1
+  | ^^^^^
+  = note: prefix it with `_` to silence this warning
+";
+    assert_eq!(render(&diag, "total = 1"), expected);
+}
+
+#[test]
+fn test_render_crlf_source() {
+    let expected = "\
+error: Unrecognized token starting at position 11: '$'
+ --> 2:5
+  |
+2 | y = $
+  |     ^
+";
+    assert_eq!(render_lex_error("x = 1\r\ny = $\r\nz = 2\r\n"), expected);
+}
+
+#[test]
+fn test_lone_carriage_return_is_one_column() {
+    assert_eq!(line_col("a\rb", 2), (1, 3));
+    assert_eq!(line_col("a\r\nb", 3), (2, 1));
+
+    let expected = "\
+error: Unrecognized token starting at position 4: '$'
+ --> 1:5
+  |
+1 | a b $
+  |     ^
+";
+    assert_eq!(render_lex_error("a\rb $"), expected);
+}
+
+#[test]
+fn test_render_unknown_operator_suggestion() {
+    let expected = "\
+error: Unknown operator `&` at position 11; did you mean `&&`?
+ --> 2:3
+  |
+2 | a & b
+  |   ^
+";
+    assert_eq!(render_lex_error("x = true\na & b"), expected);
+}
+
+#[test]
+fn test_to_json() {
+    let source = "a\nb $ \"\\q\"";
+    let error = extract_tokens_with_spans(source).unwrap_err();
+    let diag = Diagnostic::from(error).with_note("remove it");
+
+    assert_eq!(
+        to_json(&diag, "dir\\in.txt", source),
+        "{\"severity\": \"error\", \"code\": \"L0001\", \
+         \"message\": \"Unrecognized token starting at position 4: '$'\\nnote: remove it\", \
+         \"file\": \"dir\\\\in.txt\", \"line\": 2, \"column\": 3, \"end_line\": 2, \
+         \"end_column\": 4}"
+    );
+    assert_eq!(
+        to_json(&Diagnostic::warning("w", Span::new(0, 1)), "f", source),
+        "{\"severity\": \"warning\", \"code\": null, \"message\": \"w\", \"file\": \"f\", \
+         \"line\": 1, \"column\": 1, \"end_line\": 1, \"end_column\": 2}"
+    );
+}
+
+/// Parses a flat JSON object with string, integer and null values
+fn parse_json_object(line: &str) -> Vec<(String, String)> {
+    fn parse_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+        assert_eq!(chars.next(), Some('"'));
+        let mut value = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return value,
+                '\\' => match chars.next().unwrap() {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    'r' => value.push('\r'),
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+        panic!("unterminated string in {:?}", value);
+    }
+
+    let mut chars = line.chars().peekable();
+    let mut fields = Vec::new();
+    assert_eq!(chars.next(), Some('{'));
+    loop {
+        let key = parse_string(&mut chars);
+        assert_eq!(chars.next(), Some(':'));
+        assert_eq!(chars.next(), Some(' '));
+        let value = if chars.peek() == Some(&'"') {
+            parse_string(&mut chars)
+        } else {
+            let mut raw = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_alphanumeric()) {
+                raw.push(c);
+            }
+            raw
+        };
+        fields.push((key, value));
+        match chars.next() {
+            Some(',') => assert_eq!(chars.next(), Some(' ')),
+            Some('}') => break,
+            other => panic!("unexpected {:?} in {:?}", other, line),
+        }
+    }
+    assert_eq!(chars.next(), None);
+
+    fields
+}
+
+#[test]
+fn test_cli_json_diagnostics() {
+    let path = std::env::temp_dir().join(format!("lexer_json_{}.txt", std::process::id()));
+    std::fs::write(&path, "whlie x\ny = $").unwrap();
+    let file = path.to_string_lossy().to_string();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg(&path)
+        .args(["--error-format", "json"])
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let diagnostics: Vec<_> = stderr.lines().map(parse_json_object).collect();
+
+    let field = |name: &str, value: &str| (name.to_string(), value.to_string());
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        diagnostics,
+        vec![
+            vec![
+                field("severity", "warning"),
+                field("code", "W0001"),
+                field(
+                    "message",
+                    "`whlie` looks like a misspelling of the keyword `while`"
+                ),
+                field("file", &file),
+                field("line", "1"),
+                field("column", "1"),
+                field("end_line", "1"),
+                field("end_column", "6"),
+            ],
+            vec![
+                field("severity", "error"),
+                field("code", "L0001"),
+                field("message", "Unrecognized token starting at position 12: '$'"),
+                field("file", &file),
+                field("line", "2"),
+                field("column", "5"),
+                field("end_line", "2"),
+                field("end_column", "6"),
+            ],
+        ]
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_cli_exit_codes() {
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .args(args)
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+    let path = std::env::temp_dir().join(format!("lexer_exit_{}.txt", std::process::id()));
+    std::fs::write(&path, "x = 1").unwrap();
+    let path = path.to_str().unwrap();
+
+    assert_eq!(run(&[path]), Some(0));
+    assert_eq!(run(&[path, "--error-format", "xml"]), Some(2));
+    assert_eq!(run(&[path, "/nonexistent/file.txt"]), Some(2));
+    assert_eq!(run(&["diff", path, "/nonexistent/file.txt"]), Some(2));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_decode_source_reports_invalid_utf8() {
+    assert_eq!(decode_source("x = 1".as_bytes()), Ok("x = 1"));
+    assert_eq!(
+        decode_source(b"x = \xff 1"),
+        Err(LexError {
+            kind: LexErrorKind::InvalidUtf8,
+            span: Span::new(4, 5),
+        })
+    );
+}
+
+#[test]
+fn test_cli_invalid_utf8() {
+    let path = std::env::temp_dir().join(format!("lexer_utf8_{}.txt", std::process::id()));
+    std::fs::write(&path, b"x = 1\ny = \xff\n").unwrap();
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let file = path.to_str().unwrap();
+
+    for output in [
+        run(&[file]),
+        run(&["diff", file, file]),
+        run(&["check", file]),
+    ] {
+        assert_eq!(output.status.code(), Some(1));
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        assert!(stderr.starts_with("error: Invalid UTF-8 at position 10\n"));
+        assert!(stderr.contains(&format!("--> {}:2:5\n", file)));
+    }
+    let json = String::from_utf8(run(&[file, "--error-format", "json"]).stderr).unwrap();
+    assert!(json.contains("\"code\": \"L0009\""));
+
+    std::fs::remove_file(path).unwrap();
+}
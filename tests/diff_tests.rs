@@ -0,0 +1,192 @@
+use compiler_project_tc3002_b::Span;
+use compiler_project_tc3002_b::diff::*;
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn test_identical_tokens_with_different_whitespace() {
+    let a = "while x < 10\n    x += 1";
+    let b = "while   x <   10 x\t+= 1\n\n";
+
+    assert!(diff_tokens(a, b).unwrap().is_empty());
+}
+
+#[test]
+fn test_changed_literal_is_one_hunk() {
+    let a = "x = 1\ny = 2\nz = 3\n";
+    let b = "x = 1\ny = 5\nz = 3\n";
+    let diff = diff_tokens(a, b).unwrap();
+
+    assert_eq!(
+        diff.hunks,
+        vec![Hunk {
+            kind: HunkKind::Changed,
+            a: Span::new(10, 11),
+            b: Span::new(10, 11),
+        }]
+    );
+    assert_eq!(
+        format_diff(&diff, "a.txt", a, "b.txt", b),
+        "--- a.txt\n+++ b.txt\n@@ -2:5 +2:5 @@ Changed\n- 2\n+ 5\n"
+    );
+}
+
+#[test]
+fn test_inserted_and_deleted_hunks() {
+    let a = "x = 1 y = 2";
+    let b = "y = 2 z = 3";
+    let diff = diff_tokens(a, b).unwrap();
+
+    assert_eq!(
+        diff.hunks,
+        vec![
+            Hunk {
+                kind: HunkKind::Deleted,
+                a: Span::new(0, 5),
+                b: Span::new(0, 0),
+            },
+            Hunk {
+                kind: HunkKind::Inserted,
+                a: Span::new(11, 11),
+                b: Span::new(6, 11),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_ignore_identifier_names() {
+    let a = "total = total + count";
+    let b = "sum = sum + n";
+    let renamed_differently = "sum = n + n";
+    let options = DiffOptions {
+        ignore_identifier_names: true,
+    };
+
+    assert!(!diff_tokens(a, b).unwrap().is_empty());
+    assert!(diff_tokens_with_options(a, b, &options).unwrap().is_empty());
+    assert!(
+        !diff_tokens_with_options(a, renamed_differently, &options)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_diff_command_exit_codes() {
+    let dir = std::env::temp_dir().join(format!("lexer_diff_cli_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let a = dir.join("a.txt");
+    let b = dir.join("b.txt");
+    let c = dir.join("c.txt");
+    fs::write(&a, "x = 1").unwrap();
+    fs::write(&b, "x   =\n1").unwrap();
+    fs::write(&c, "x = 2").unwrap();
+    let broken = dir.join("broken.txt");
+    fs::write(&broken, "x = $").unwrap();
+
+    let run = |other: &std::path::Path| {
+        Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .arg("diff")
+            .arg(&a)
+            .arg(other)
+            .output()
+            .unwrap()
+    };
+
+    let same = run(&b);
+    assert_eq!(same.status.code(), Some(0));
+    assert!(same.stdout.is_empty());
+
+    let different = run(&c);
+    assert_eq!(different.status.code(), Some(1));
+    assert!(
+        String::from_utf8(different.stdout)
+            .unwrap()
+            .contains("- 1\n+ 2\n")
+    );
+
+    let lex_error = run(&broken);
+    assert_eq!(lex_error.status.code(), Some(1));
+    assert!(lex_error.stdout.is_empty());
+    let stderr = String::from_utf8(lex_error.stderr).unwrap();
+    assert!(stderr.contains(&format!("--> {}:1:5", broken.display())));
+    assert!(stderr.starts_with("error: Unrecognized token"));
+
+    let json = Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg("diff")
+        .arg(&broken)
+        .arg(&a)
+        .args(["--error-format", "json"])
+        .output()
+        .unwrap();
+    assert_eq!(json.status.code(), Some(1));
+    assert!(
+        String::from_utf8(json.stderr)
+            .unwrap()
+            .contains("\"code\": \"L0001\"")
+    );
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_diff_reports_which_source_fails() {
+    assert!(matches!(
+        diff_tokens("x = $", "x"),
+        Err(DiffError::First(_))
+    ));
+    assert!(matches!(
+        diff_tokens("x", "x = $"),
+        Err(DiffError::Second(_))
+    ));
+}
+
+#[test]
+fn test_ignore_identifier_names_in_interpolations() {
+    let a = "total = 0 print \"sum: ${total + n}\"";
+    let b = "sum = 0 print \"sum: ${sum + m}\"";
+    let swapped = "sum = 0 print \"sum: ${m + sum}\"";
+    let options = DiffOptions {
+        ignore_identifier_names: true,
+    };
+
+    assert!(diff_tokens_with_options(a, b, &options).unwrap().is_empty());
+    assert!(
+        !diff_tokens_with_options(a, swapped, &options)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_diff_of_long_sources() {
+    let a = "x = 1\n".repeat(1000);
+    let b = format!(
+        "y = 2\n{}x = 2\n{}y = 2\n",
+        "x = 1\n".repeat(500),
+        "x = 1\n".repeat(499)
+    );
+    let diff = diff_tokens(&a, &b).unwrap();
+
+    assert_eq!(
+        diff.hunks,
+        vec![
+            Hunk {
+                kind: HunkKind::Inserted,
+                a: Span::new(0, 0),
+                b: Span::new(0, 5),
+            },
+            Hunk {
+                kind: HunkKind::Changed,
+                a: Span::new(3004, 3005),
+                b: Span::new(3010, 3011),
+            },
+            Hunk {
+                kind: HunkKind::Inserted,
+                a: Span::new(6000, 6000),
+                b: Span::new(6006, 6011),
+            },
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+use compiler_project_tc3002_b::Span;
+use compiler_project_tc3002_b::docs::*;
+
+const SOURCE: &str = "\
+#!/usr/bin/env lang // not a doc comment
+/// Adds one to `x`
+/// and returns it
+fn increment x
+    x + 1
+
+fn helper y
+    y
+
+/**
+ * Doubles `x`
+ */
+fn double x
+    x * 2
+
+/// Detached by the blank line
+
+fn plain
+
+/// Detached by the ordinary comment
+// ordinary
+fn other
+
+// ordinary
+/// Only this line
+fn last
+
+/// Nothing follows
+";
+
+/// Returns the span from the start of `first` to the end of `last` in the source
+fn span_between(first: &str, last: &str) -> Span {
+    let start = SOURCE.find(first).unwrap();
+    let end = SOURCE.find(last).unwrap() + last.len();
+    Span::new(start, end)
+}
+
+#[test]
+fn test_extract_docs() {
+    let docs = extract_docs(SOURCE).unwrap();
+
+    assert_eq!(
+        docs,
+        vec![
+            DocEntry {
+                item_name: "increment".to_string(),
+                kind: ItemKind::Function,
+                text: "Adds one to `x`\nand returns it".to_string(),
+                span: span_between("/// Adds", "and returns it"),
+            },
+            DocEntry {
+                item_name: "double".to_string(),
+                kind: ItemKind::Function,
+                text: "Doubles `x`".to_string(),
+                span: span_between("/**", "*/"),
+            },
+            DocEntry {
+                item_name: "last".to_string(),
+                kind: ItemKind::Function,
+                text: "Only this line".to_string(),
+                span: span_between("/// Only", "this line"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_extract_docs_ordinary_comments() {
+    let source = "//// four slashes\nfn a\n/*** three stars */\nfn b\n/**/\nfn c";
+
+    assert_eq!(extract_docs(source).unwrap(), vec![]);
+}
+
+#[test]
+fn test_docs_to_json() {
+    let docs = extract_docs("/// Says \"hi\"\n///\tand more\nfn greet").unwrap();
+
+    assert_eq!(
+        docs_to_json(&docs),
+        "[\n  {\"name\": \"greet\", \"kind\": \"function\", \
+         \"text\": \"Says \\\"hi\\\"\\n\\tand more\", \"start\": 0, \"end\": 26}\n]\n"
+    );
+    assert_eq!(docs_to_json(&[]), "[]\n");
+}
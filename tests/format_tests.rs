@@ -0,0 +1,138 @@
+use compiler_project_tc3002_b::format::*;
+use compiler_project_tc3002_b::{Token, extract_tokens_with_spans};
+use std::fs;
+use std::path::Path;
+
+const SAMPLES: [&str; 5] = [
+    "fn total total\n    total = total + 1\nfn main\n    x = total 2\n",
+    "if   x\t<   3 \n\n\n\n      y   =   y +   1   \nelse\n\t\ty = [1 ,2,3 ]\n",
+    "xs [ 0 ] = ! done && \"a b\" [ 1 ]\r\n// trailing   \r\n",
+    "/* header\n   comment */\nwhile x <= 10 // count up\n        x += 1 /* step */\n\n\n",
+    "#!/usr/bin/env lexer\nprint \"${ n  +  1 }\"\n  if x\n      if y\n  z\n",
+];
+
+/// Returns the tokens of a source other than whitespace
+fn tokens(source: &str) -> Vec<Token> {
+    extract_tokens_with_spans(source)
+        .unwrap()
+        .into_iter()
+        .map(|(token, _)| token)
+        .filter(|token| *token != Token::Whitespace)
+        .collect()
+}
+
+fn format(source: &str) -> String {
+    format_source(source, &FormatStyle::default()).unwrap()
+}
+
+#[test]
+fn test_format_keeps_tokens() {
+    for source in SAMPLES {
+        assert_eq!(tokens(&format(source)), tokens(source), "{:?}", source);
+    }
+}
+
+#[test]
+fn test_format_is_idempotent() {
+    for source in SAMPLES {
+        let formatted = format(source);
+        assert_eq!(format(&formatted), formatted, "{:?}", source);
+    }
+}
+
+#[test]
+fn test_format_pathological_spacing() {
+    assert_eq!(
+        format(SAMPLES[1]),
+        "if x < 3\n\n    y = y + 1\nelse\n    y = [1, 2, 3]\n"
+    );
+    assert_eq!(
+        format(SAMPLES[2]),
+        "xs[0] = ! done && \"a b\"[1]\n// trailing\n"
+    );
+}
+
+#[test]
+fn test_format_comments() {
+    assert_eq!(
+        format(SAMPLES[3]),
+        "/* header\n   comment */\nwhile x <= 10 // count up\n    x += 1 /* step */\n"
+    );
+}
+
+#[test]
+fn test_format_indentation_levels() {
+    assert_eq!(
+        format(SAMPLES[4]),
+        "#!/usr/bin/env lexer\nprint \"${ n  +  1 }\"\n    if x\n        if y\n    z\n"
+    );
+}
+
+#[test]
+fn test_format_style() {
+    let style = FormatStyle {
+        indent_width: 2,
+        max_blank_lines: 2,
+    };
+    assert_eq!(
+        format_source("a\n\n\n\n\n    b\n", &style).unwrap(),
+        "a\n\n\n  b\n"
+    );
+}
+
+#[test]
+fn test_format_empty() {
+    assert_eq!(format(""), "");
+    assert_eq!(format(" \n\n\t"), "");
+}
+
+#[test]
+fn test_format_lex_error() {
+    assert!(format_source("x = @", &FormatStyle::default()).is_err());
+}
+
+/// Returns the samples above and every case in `tests/cases` that lexes
+fn corpus() -> Vec<String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cases");
+    let mut sources: Vec<String> = SAMPLES.iter().map(|source| source.to_string()).collect();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        if path.extension().is_some_and(|ext| ext == "src")
+            && extract_tokens_with_spans(&source).is_ok()
+        {
+            sources.push(source);
+        }
+    }
+    sources
+}
+
+#[test]
+fn test_minify_keeps_tokens() {
+    for source in corpus() {
+        let minified = minify(&source).unwrap();
+        assert_eq!(tokens(&minified), tokens(&source), "{:?}", source);
+        assert_eq!(minify(&minified).unwrap(), minified, "{:?}", source);
+    }
+}
+
+#[test]
+fn test_minify_separators() {
+    assert_eq!(minify("a  -  - b\n").unwrap(), "a - - b");
+    assert_eq!(minify("a   -   -5").unwrap(), "a - -5");
+    assert_eq!(
+        minify("while   done\n\tx += 1").unwrap(),
+        "while done x += 1"
+    );
+    assert_eq!(minify("1  2.5\n3").unwrap(), "1 2.5 3");
+    assert_eq!(minify("xs [ 0 ] = [ 1 , 2 ]").unwrap(), "xs[0]=[1,2]");
+}
+
+#[test]
+fn test_minify_comments() {
+    assert_eq!(
+        minify("#!/usr/bin/env lexer\n// note\nx /* a */ = /* b */ 1 // c\n").unwrap(),
+        "#!/usr/bin/env lexer\nx = 1"
+    );
+    assert_eq!(minify("  // only a comment\n").unwrap(), "");
+}
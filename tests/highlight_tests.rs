@@ -0,0 +1,76 @@
+use compiler_project_tc3002_b::highlight::*;
+
+/// Removes every HTML tag and decodes the escaped characters
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+#[test]
+fn test_highlight_html_snapshot() {
+    let source = "if x < 10 && ! done\n\tx += 1.5\ns = \"a\" \"${x}\"";
+
+    assert_eq!(
+        highlight_html(source).unwrap(),
+        "<span class=\"kw\">if</span> <span class=\"ident\">x</span> \
+         <span class=\"op\">&lt;</span> <span class=\"num\">10</span> \
+         <span class=\"op\">&amp;&amp;</span> <span class=\"op\">!</span> \
+         <span class=\"ident\">done</span>\n\t<span class=\"ident\">x</span> \
+         <span class=\"op\">+=</span> <span class=\"num\">1.5</span>\n\
+         <span class=\"ident\">s</span> <span class=\"op\">=</span> \
+         <span class=\"string\">&quot;a&quot;</span> \
+         <span class=\"string\">&quot;${x}&quot;</span>"
+    );
+}
+
+#[test]
+fn test_highlight_html_comments_snapshot() {
+    let source = "#!/bin/lexer // not a comment\nx // a < b\n/* one\ntwo */ y";
+
+    assert_eq!(
+        highlight_html(source).unwrap(),
+        "#!/bin/lexer // not a comment\n<span class=\"ident\">x</span> \
+         <span class=\"comment\">// a &lt; b</span>\n\
+         <span class=\"comment\">/* one\ntwo */</span> <span class=\"ident\">y</span>"
+    );
+}
+
+#[test]
+fn test_highlight_html_preserves_source_text() {
+    let sources = [
+        "if x < 10 && ! done\n\tx += 1.5",
+        "  while   a >= -3\n\n",
+        "fn f\r",
+        "a /* b */ // c\n/**/d",
+    ];
+
+    for source in sources {
+        assert_eq!(strip_tags(&highlight_html(source).unwrap()), source);
+    }
+}
+
+#[test]
+fn test_highlight_html_reports_lex_errors() {
+    assert!(highlight_html("x = $").is_err());
+}
+
+#[test]
+fn test_escape_html() {
+    assert_eq!(
+        escape_html("a < b && \"c\" > d"),
+        "a &lt; b &amp;&amp; &quot;c&quot; &gt; d"
+    );
+}
@@ -0,0 +1,254 @@
+use compiler_project_tc3002_b::diagnostic::{RenderOptions, render_in_file};
+use compiler_project_tc3002_b::include::*;
+use compiler_project_tc3002_b::{LexErrorKind, Span, Token};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Creates a fresh temporary directory holding the given files
+fn setup_directory(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("lexer_include_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    for (path, contents) in files {
+        fs::write(dir.join(path), contents).unwrap();
+    }
+
+    dir
+}
+
+/// Returns the text of each non-whitespace token along with the name of its file
+fn located_text(tokens: &[(Token, FileSpan)], map: &SourceMap) -> Vec<(String, String)> {
+    tokens
+        .iter()
+        .filter(|(token, _)| *token != Token::Whitespace)
+        .map(|(_, location)| {
+            let file = map.get(location.file);
+            let name = file.path.file_name().unwrap().to_string_lossy();
+            let span = location.span;
+            (
+                name.to_string(),
+                file.source[span.start..span.end].to_string(),
+            )
+        })
+        .collect()
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(file, text)| (file.to_string(), text.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_include_chain() {
+    let dir = setup_directory(
+        "chain",
+        &[
+            ("main.lang", "include \"lib/a.lang\"\nx = a"),
+            ("lib/a.lang", "include \"b.lang\"\na = b + 1\n"),
+            ("lib/b.lang", "b = 2\n"),
+        ],
+    );
+    let mut map = SourceMap::new();
+
+    let tokens = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap();
+
+    assert_eq!(
+        located_text(&tokens, &map),
+        pairs(&[
+            ("b.lang", "b"),
+            ("b.lang", "="),
+            ("b.lang", "2"),
+            ("a.lang", "a"),
+            ("a.lang", "="),
+            ("a.lang", "b"),
+            ("a.lang", "+"),
+            ("a.lang", "1"),
+            ("main.lang", "x"),
+            ("main.lang", "="),
+            ("main.lang", "a"),
+        ])
+    );
+    assert_eq!(map.len(), 3);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_diamond_include() {
+    let dir = setup_directory(
+        "diamond",
+        &[
+            (
+                "main.lang",
+                "include \"left.lang\"\ninclude \"right.lang\"\n",
+            ),
+            ("left.lang", "include \"base.lang\"\nl\n"),
+            ("right.lang", "include \"base.lang\"\nr\n"),
+            ("base.lang", "base\n"),
+        ],
+    );
+    let mut map = SourceMap::new();
+
+    let tokens = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap();
+
+    assert_eq!(
+        located_text(&tokens, &map),
+        pairs(&[
+            ("base.lang", "base"),
+            ("left.lang", "l"),
+            ("base.lang", "base"),
+            ("right.lang", "r"),
+        ])
+    );
+    // The shared file is only read once
+    assert_eq!(map.len(), 4);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_include_cycle() {
+    let dir = setup_directory(
+        "cycle",
+        &[
+            ("main.lang", "include \"a.lang\"\n"),
+            ("a.lang", "x = 1\ninclude \"b.lang\"\n"),
+            ("b.lang", "include \"a.lang\"\n"),
+        ],
+    );
+    let mut map = SourceMap::new();
+
+    let error = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap_err();
+
+    let IncludeError::Cycle { path, include } = &error else {
+        panic!("expected a cycle, got {:?}", error);
+    };
+    assert!(path.ends_with("a.lang"));
+    assert!(map.path(include.file).ends_with("b.lang"));
+    assert_eq!(include.span, Span::new(0, 16));
+    let (_, diag) = error.diagnostic().unwrap();
+    assert_eq!(diag.code, Some(INCLUDE_CYCLE));
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_missing_include() {
+    let dir = setup_directory(
+        "missing",
+        &[("main.lang", "x = 1\n  include \"nope.lang\"\n")],
+    );
+    let mut map = SourceMap::new();
+
+    let error = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap_err();
+
+    let (file, diag) = error.diagnostic().unwrap();
+    assert_eq!(map.path(file), dir.join("main.lang"));
+    assert_eq!(diag.span, Span::new(8, 27));
+    assert_eq!(diag.code, Some(MISSING_INCLUDE));
+    assert!(diag.message.starts_with("Cannot include "));
+
+    let error = lex_with_includes(&dir.join("other.lang"), &mut SourceMap::new()).unwrap_err();
+    assert!(matches!(error, IncludeError::Read { include: None, .. }));
+    assert!(error.diagnostic().is_none());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_error_in_included_file() {
+    let dir = setup_directory(
+        "error",
+        &[
+            ("main.lang", "include \"lib/util.lang\"\n"),
+            ("lib/util.lang", "a = 1\nb = $\n"),
+        ],
+    );
+    let mut map = SourceMap::new();
+
+    let error = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap_err();
+
+    assert!(matches!(
+        &error,
+        IncludeError::Lex { error, .. } if error.kind == LexErrorKind::UnrecognizedToken('$')
+    ));
+    let (file, diag) = error.diagnostic().unwrap();
+    let name = Path::new("lib").join("util.lang");
+    assert!(map.path(file).ends_with(&name));
+    let rendered = render_in_file(
+        &diag,
+        &name.to_string_lossy(),
+        map.source(file),
+        &RenderOptions::default(),
+    );
+    let expected = format!(
+        "\
+error: Unrecognized token starting at position 10: '$'
+ --> {}:2:5
+  |
+2 | b = $
+  |     ^
+",
+        name.display()
+    );
+    assert_eq!(rendered, expected);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_include_only_at_line_start() {
+    let dir = setup_directory("position", &[("main.lang", "x = include \"a.lang\"\n")]);
+    let mut map = SourceMap::new();
+
+    let tokens = lex_with_includes(&dir.join("main.lang"), &mut map).unwrap();
+
+    assert!(tokens.contains(&(
+        Token::Identifier("include".to_string()),
+        FileSpan {
+            file: tokens[0].1.file,
+            span: Span::new(4, 11),
+        }
+    )));
+    assert_eq!(map.len(), 1);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_cli_includes() {
+    let dir = setup_directory(
+        "cli",
+        &[
+            ("main.lang", "include \"lib/a.lang\"\n"),
+            ("lib/a.lang", "a = $\n"),
+            ("ok.lang", "include \"lib/b.lang\"\n"),
+            ("lib/b.lang", "b\n"),
+        ],
+    );
+    let run = |file: &str, color: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .arg(dir.join(file))
+            .arg("--includes")
+            .arg(color)
+            .output()
+            .unwrap()
+    };
+
+    let output = run("ok.lang", "--color=never");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Token: Identifier(\"b\")\n"
+    );
+
+    let output = run("main.lang", "--color=never");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!("{}:1:5", dir.join("lib/a.lang").display())));
+    assert!(!stderr.contains('\x1b'));
+
+    let output = run("main.lang", "--color=always");
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("\x1b[31m")
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,128 @@
+use compiler_project_tc3002_b::extract_tokens_with_spans;
+use compiler_project_tc3002_b::incremental::*;
+
+/// Applies an edit to `old_text` and checks `relex` against lexing the new text from scratch
+fn check_edit(old_text: &str, start: usize, old_len: usize, replacement: &str) {
+    let previous = extract_tokens_with_spans(old_text).unwrap();
+    let new_text = format!(
+        "{}{}{}",
+        &old_text[..start],
+        replacement,
+        &old_text[start + old_len..]
+    );
+    let edit = Edit {
+        start,
+        old_len,
+        new_len: replacement.len(),
+    };
+
+    assert_eq!(
+        relex(&previous, old_text, &new_text, edit),
+        extract_tokens_with_spans(&new_text),
+        "editing {:?} into {:?}",
+        old_text,
+        new_text
+    );
+}
+
+#[test]
+fn test_relex_inside_number() {
+    check_edit("x = 1234 + y", 6, 0, "9");
+    check_edit("x = 1234 + y", 6, 1, ".");
+    check_edit("x = 12 + y", 6, 0, ".5");
+}
+
+#[test]
+fn test_relex_at_start_and_end() {
+    check_edit("while x < 10 x += 1", 0, 0, "fn f ");
+    check_edit("while x < 10 x += 1", 0, 5, "for");
+    check_edit("while x < 10 x += 1", 19, 0, " y");
+    check_edit("while x < 10 x += 1", 18, 1, "2.5");
+}
+
+#[test]
+fn test_relex_merging_and_splitting_tokens() {
+    check_edit("ab cd", 2, 1, "");
+    check_edit("abcd", 2, 0, " ");
+    check_edit("x = a", 3, 1, "");
+    check_edit("x = 1 + 2", 5, 0, "2");
+}
+
+#[test]
+fn test_relex_into_error() {
+    check_edit("x = 1 + 2", 4, 0, "$");
+    check_edit("x = 1 + 2", 5, 1, "");
+}
+
+#[test]
+fn test_relex_reuses_unchanged_tail() {
+    let old_text = "a = 1 b = 2 c = 3";
+    let new_text = "a = 10 b = 2 c = 3";
+    let previous = extract_tokens_with_spans(old_text).unwrap();
+    let edit = Edit {
+        start: 5,
+        old_len: 0,
+        new_len: 1,
+    };
+
+    let tokens = relex(&previous, old_text, new_text, edit).unwrap();
+
+    assert_eq!(tokens, extract_tokens_with_spans(new_text).unwrap());
+}
+
+/// Small deterministic pseudo-random generator (xorshift64)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+}
+
+const PIECES: &[&str] = &[
+    "x", "ab", "while", "fn", "if", "else", "1", "42", "-7", "3.5", "+", "-", "=", "==", "<=",
+    "&&", "!", " ", "  ", "\n", "\t", ".", "9", "_",
+];
+
+fn random_text(rng: &mut Rng, pieces: usize) -> String {
+    let mut text = String::new();
+    for _ in 0..pieces {
+        text.push_str(rng.pick(PIECES));
+        if rng.below(3) > 0 {
+            text.push(' ');
+        }
+    }
+    text
+}
+
+#[test]
+fn test_relex_matches_full_lex_on_random_edits() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut checked = 0;
+
+    while checked < 2000 {
+        let old_text = random_text(&mut rng, 12);
+        if extract_tokens_with_spans(&old_text).is_err() {
+            continue;
+        }
+
+        let start = rng.below(old_text.len() + 1);
+        let old_len = rng.below(old_text.len() - start + 1).min(4);
+        let pieces = rng.below(3);
+        let replacement = random_text(&mut rng, pieces);
+
+        check_edit(&old_text, start, old_len, &replacement);
+        checked += 1;
+    }
+}
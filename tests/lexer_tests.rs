@@ -1,4 +1,9 @@
 use compiler_project_tc3002_b::*;
+use std::collections::HashSet;
+
+fn decimal(text: &str) -> Token {
+    Token::Decimal(DecimalLit::new(text).unwrap())
+}
 
 #[test]
 fn test_parse_keyword() {
@@ -6,6 +11,8 @@ fn test_parse_keyword() {
     assert_eq!(parse_keyword("for"), Some(Keyword::For));
     assert_eq!(parse_keyword("fn"), Some(Keyword::Fn));
     assert_eq!(parse_keyword("if"), Some(Keyword::If));
+    assert_eq!(parse_keyword("in"), Some(Keyword::In));
+    assert_eq!(parse_keyword("as"), Some(Keyword::As));
     assert_eq!(parse_keyword("unknown_keyword"), None);
 }
 
@@ -18,13 +25,89 @@ fn test_parse_operator() {
     assert_eq!(parse_operator("not_an_op"), None);
 }
 
+#[test]
+fn test_signed_numbers_and_unary_operators() {
+    let minus = Token::Operator(Operator::Minus);
+    let cases = [
+        ("- 5", vec![minus.clone(), Token::Integer(5)]),
+        ("-5", vec![Token::Integer(-5)]),
+        (
+            "a - 5",
+            vec![
+                Token::Identifier("a".to_string()),
+                minus.clone(),
+                Token::Integer(5),
+            ],
+        ),
+        // Without a space after `-` the sign belongs to the literal, leaving two operands
+        (
+            "a -5",
+            vec![Token::Identifier("a".to_string()), Token::Integer(-5)],
+        ),
+        ("- -5", vec![minus.clone(), Token::Integer(-5)]),
+        (
+            "- - x",
+            vec![
+                minus.clone(),
+                minus.clone(),
+                Token::Identifier("x".to_string()),
+            ],
+        ),
+        ("-5.5", vec![decimal("-5.5")]),
+        ("-9223372036854775808", vec![Token::Integer(i64::MIN)]),
+        (
+            "! done",
+            vec![
+                Token::Operator(Operator::Not),
+                Token::Identifier("done".to_string()),
+            ],
+        ),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(extract_tokens_filtered(input), expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_unary_operators_need_separators() {
+    for input in ["-x", "--x", "!done", "a-5"] {
+        let error = extract_tokens_with_spans(input).unwrap_err();
+        assert!(
+            matches!(error.kind, LexErrorKind::MissingSeparator { .. }),
+            "{:?}",
+            input
+        );
+    }
+    // There are no parentheses, so `-(5)` cannot be written
+    assert_eq!(
+        extract_tokens_with_spans("-(5)").unwrap_err().kind,
+        LexErrorKind::UnrecognizedToken('(')
+    );
+    // Only the literal `i64::MIN` keeps its sign; `- 9223372036854775808` overflows
+    assert_eq!(
+        extract_tokens_with_spans("- 9223372036854775808")
+            .unwrap_err()
+            .kind,
+        LexErrorKind::IntegerOverflow
+    );
+}
+
+#[test]
+fn test_compound_base() {
+    for op in ["+=", "-=", "*=", "/="] {
+        let base = parse_operator(op).unwrap().compound_base().unwrap();
+        assert_eq!(format!("{}=", base.as_str()), op);
+    }
+    assert_eq!(Operator::Equal.compound_base(), None);
+    assert_eq!(Operator::Plus.compound_base(), None);
+    assert_eq!(Operator::LessEqual.compound_base(), None);
+}
+
 #[test]
 fn test_extract_tokens_basic() {
-    let input = "fn myFunc 42 + 3.14 while".to_string();
-    let tokens = extract_tokens(input)
-        .into_iter()
-        .filter(|t| !matches!(t, Token::Whitespace))
-        .collect::<Vec<_>>();
+    let input = "fn myFunc 42 + 3.14 while";
+    let tokens = extract_tokens_filtered(input);
 
     assert_eq!(
         tokens,
@@ -33,7 +116,7 @@ fn test_extract_tokens_basic() {
             Token::Identifier("myFunc".to_string()),
             Token::Integer(42),
             Token::Operator(Operator::Plus),
-            Token::Decimal(3.14),
+            decimal("3.14"),
             Token::Keyword(Keyword::While),
         ]
     );
@@ -57,11 +140,8 @@ fn test_unsupported_token_panic() {
 
 #[test]
 fn test_token_with_multiple_whitespace() {
-    let input = "if    x   !=  10".to_string();
-    let tokens = extract_tokens(input)
-        .into_iter()
-        .filter(|t| !matches!(t, Token::Whitespace))
-        .collect::<Vec<_>>();
+    let input = "if    x   !=  10";
+    let tokens = extract_tokens_filtered(input);
 
     assert_eq!(
         tokens,
@@ -76,29 +156,571 @@ fn test_token_with_multiple_whitespace() {
 
 #[test]
 fn test_unrecognized_identifier_is_treated_as_identifier() {
-    let input = "foobar".to_string();
-    let tokens = extract_tokens(input)
-        .into_iter()
-        .filter(|t| !matches!(t, Token::Whitespace))
-        .collect::<Vec<_>>();
+    let input = "foobar";
+    let tokens = extract_tokens_filtered(input);
 
     assert_eq!(tokens, vec![Token::Identifier("foobar".to_string())]);
 }
 
 #[test]
 fn test_integer_and_decimal_literals() {
-    let input = "100 -42 3.1415".to_string();
-    let tokens = extract_tokens(input)
-        .into_iter()
-        .filter(|t| !matches!(t, Token::Whitespace))
-        .collect::<Vec<_>>();
+    let input = "100 -42 3.1415";
+    let tokens = extract_tokens_filtered(input);
+
+    assert_eq!(
+        tokens,
+        vec![Token::Integer(100), Token::Integer(-42), decimal("3.1415"),]
+    );
+}
+
+#[test]
+fn test_extract_tokens_with_spans() {
+    let tokens = extract_tokens_with_spans("x  += 2.5").unwrap();
+
+    assert_eq!(
+        tokens,
+        vec![
+            (Token::Identifier("x".to_string()), Span::new(0, 1)),
+            (Token::Whitespace, Span::new(1, 3)),
+            (Token::Operator(Operator::PlusEqual), Span::new(3, 5)),
+            (Token::Whitespace, Span::new(5, 6)),
+            (decimal("2.5"), Span::new(6, 9)),
+        ]
+    );
+}
+
+#[test]
+fn test_borrowed_tokens_point_into_input() {
+    let input = String::from("fn total_sum while counter");
+    let tokens = extract_tokens_borrowed(&input);
+    let input_range = input.as_bytes().as_ptr_range();
+
+    let identifiers: Vec<&str> = tokens
+        .iter()
+        .filter_map(|t| match t {
+            BorrowedToken::Identifier(name) => Some(*name),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(identifiers, vec!["total_sum", "counter"]);
+    for name in identifiers {
+        let name_range = name.as_bytes().as_ptr_range();
+        assert!(input_range.start <= name_range.start && name_range.end <= input_range.end);
+    }
+}
+
+#[test]
+fn test_borrowed_tokens_match_owned_tokens() {
+    let input = "if x != 10 y = -2.5 else z";
+    let borrowed: Vec<Token> = extract_tokens_borrowed(input)
+        .iter()
+        .map(BorrowedToken::to_owned)
+        .collect();
+
+    assert_eq!(borrowed, extract_tokens(input.to_string()));
+}
+
+#[test]
+fn test_extract_tokens_still_emits_whitespace() {
+    let tokens = extract_tokens("x  = \n 1".to_string());
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("x".to_string()),
+            Token::Whitespace,
+            Token::Operator(Operator::Equal),
+            Token::Whitespace,
+            Token::Integer(1),
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Missing separator between tokens")]
+fn test_filtered_missing_separator_panic() {
+    let _ = extract_tokens_filtered("x = 42+3");
+}
+
+#[test]
+fn test_extract_tokens_with_options_skips_whitespace() {
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+    let tokens = extract_tokens_with_options("a  b", &options).unwrap();
+
+    assert_eq!(
+        tokens,
+        vec![
+            (Token::Identifier("a".to_string()), Span::new(0, 1)),
+            (Token::Identifier("b".to_string()), Span::new(3, 4)),
+        ]
+    );
+}
+
+#[test]
+fn test_traced_callback_sees_every_token() {
+    let input = "while x\n  x -= 1";
+    let mut observed = Vec::new();
+
+    let tokens = extract_tokens_traced(input, &LexerOptions::default(), |token, span| {
+        observed.push((token.clone(), span))
+    })
+    .unwrap();
+
+    assert_eq!(observed, tokens);
+    assert_eq!(tokens, extract_tokens_with_spans(input).unwrap());
+}
+
+#[test]
+fn test_traced_callback_sees_skipped_whitespace() {
+    let input = "x = 1";
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+    let mut observed = Vec::new();
+
+    let tokens = extract_tokens_traced(input, &options, |token, span| {
+        observed.push((token.clone(), span))
+    })
+    .unwrap();
+
+    assert_eq!(observed, extract_tokens_with_spans(input).unwrap());
+    assert_eq!(
+        tokens,
+        extract_tokens_with_options(input, &options).unwrap()
+    );
+    assert_eq!(observed.len(), 5);
+    assert_eq!(tokens.len(), 3);
+}
+
+#[test]
+fn test_crlf_matches_lf() {
+    let lf = "while x < 10\n    x += 1\nfn f\n";
+    let crlf = lf.replace('\n', "\r\n");
+    let options = LexerOptions {
+        emit_whitespace: false,
+    };
+
+    let lf_tokens = extract_tokens_with_options(lf, &options).unwrap();
+    let crlf_tokens = extract_tokens_with_options(&crlf, &options).unwrap();
+
+    let lines = |source: &str, tokens: &[(Token, Span)]| {
+        tokens
+            .iter()
+            .map(|(token, span)| (token.clone(), diagnostic::line_col(source, span.start)))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lines(lf, &lf_tokens), lines(&crlf, &crlf_tokens));
+    assert_eq!(diagnostic::line_col(&crlf, crlf_tokens[4].1.start), (2, 5));
+}
+
+#[test]
+fn test_other_whitespace_characters() {
+    let tokens = extract_tokens_filtered("a\rb\x0bc\x0cd \r\n");
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Identifier("b".to_string()),
+            Token::Identifier("c".to_string()),
+            Token::Identifier("d".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_bom_only() {
+    assert_eq!(extract_tokens_filtered("\u{feff}"), vec![]);
+    assert_eq!(
+        extract_tokens_with_spans("\u{feff}").unwrap(),
+        vec![(Token::Whitespace, Span::new(0, 3))]
+    );
+}
+
+#[test]
+fn test_bom_before_program() {
+    let tokens = extract_tokens_with_spans("\u{feff}x = 1").unwrap();
+
+    assert_eq!(tokens[0], (Token::Whitespace, Span::new(0, 3)));
+    assert_eq!(
+        tokens[1],
+        (Token::Identifier("x".to_string()), Span::new(3, 4))
+    );
+}
+
+#[test]
+fn test_shebang_before_program() {
+    let input = "#!/usr/bin/env mylang\nwhile x\n";
+    let tokens = extract_tokens_with_spans(input).unwrap();
+
+    assert_eq!(tokens[0], (Token::Whitespace, Span::new(0, 22)));
+    assert_eq!(tokens[1].0, Token::Keyword(Keyword::While));
+    // Spans still index the original input, so the shebang is line 1
+    assert_eq!(diagnostic::line_col(input, tokens[1].1.start), (2, 1));
+
+    assert_eq!(
+        extract_tokens_filtered("\u{feff}#!run\nx"),
+        vec![Token::Identifier("x".to_string())]
+    );
+    assert_eq!(extract_tokens_filtered("#!only a shebang"), vec![]);
+}
+
+#[test]
+fn test_hash_after_start_is_an_error() {
+    let error = extract_tokens_with_spans("x = 1\n#!not a shebang").unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('#'));
+    assert_eq!(error.span, Span::new(6, 7));
+
+    let error = extract_tokens_with_spans(" #!x").unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('#'));
+}
+
+#[test]
+fn test_unicode_identifiers() {
+    let input = "contraseña = 5\n数 + 1\nañoActual _ñ";
+    let tokens = extract_tokens_with_options(
+        input,
+        &LexerOptions {
+            emit_whitespace: false,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        tokens[0],
+        (
+            Token::Identifier("contraseña".to_string()),
+            Span::new(0, 11)
+        )
+    );
+    assert_eq!(
+        tokens[3],
+        (Token::Identifier("数".to_string()), Span::new(16, 19))
+    );
+    assert_eq!(tokens[6].0, Token::Identifier("añoActual".to_string()));
+    assert_eq!(tokens[7].0, Token::Identifier("_ñ".to_string()));
+    // Columns count characters, not bytes
+    assert_eq!(diagnostic::line_col(input, tokens[1].1.start), (1, 12));
+}
+
+#[test]
+fn test_identifier_with_combining_accent() {
+    // "e" followed by U+0301 COMBINING ACUTE ACCENT
+    let name = "cafe\u{301}";
+    let tokens = extract_tokens_filtered(&format!("{} = 1", name));
+
+    assert_eq!(tokens[0], Token::Identifier(name.to_string()));
+    assert_eq!(tokens.len(), 3);
+}
+
+#[test]
+fn test_keywords_stay_ascii() {
+    assert_eq!(
+        extract_tokens_filtered("while whileñ"),
+        vec![
+            Token::Keyword(Keyword::While),
+            Token::Identifier("whileñ".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_emoji_is_a_clean_error() {
+    let error = extract_tokens_with_spans("x = 🦀").unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('🦀'));
+    assert_eq!(error.span, Span::new(4, 8));
+
+    let error = extract_tokens_with_spans("ñ🦀").unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('🦀'));
+}
+
+#[test]
+fn test_single_ampersand_and_pipe() {
+    for (input, op, span) in [
+        ("a & b", "&", Span::new(2, 3)),
+        ("a | b", "|", Span::new(2, 3)),
+        ("a&b", "&", Span::new(1, 2)),
+    ] {
+        let error = extract_tokens_with_spans(input).unwrap_err();
+        assert_eq!(error.kind, LexErrorKind::UnknownOperator(op.to_string()));
+        assert_eq!(error.span, span, "{:?}", input);
+    }
+
+    assert_eq!(
+        extract_tokens_with_spans("a & b").unwrap_err().to_string(),
+        "Unknown operator `&` at position 2; did you mean `&&`?"
+    );
+    assert_eq!(
+        extract_tokens_with_spans("x\ny | z")
+            .unwrap_err()
+            .to_string(),
+        "Unknown operator `|` at position 4; did you mean `||`?"
+    );
+}
+
+#[test]
+fn test_operator_missing_from_table() {
+    // The operator rule also matches a backslash, which has no entry in the table
+    let error = extract_tokens_with_spans("a \\ b").unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnknownOperator("\\".to_string()));
+    assert_eq!(
+        error.to_string(),
+        "Operator `\\` at position 2 matched by lexer but missing from table"
+    );
+}
+
+#[test]
+fn test_comments_are_whitespace() {
+    let input = "x = 1 // one\ny/* two\nlines */=/**/2";
+    let tokens = extract_tokens_with_spans(input).unwrap();
 
     assert_eq!(
         tokens,
         vec![
-            Token::Integer(100),
-            Token::Integer(-42),
-            Token::Decimal(3.1415),
+            (Token::Identifier("x".to_string()), Span::new(0, 1)),
+            (Token::Whitespace, Span::new(1, 2)),
+            (Token::Operator(Operator::Equal), Span::new(2, 3)),
+            (Token::Whitespace, Span::new(3, 4)),
+            (Token::Integer(1), Span::new(4, 5)),
+            (Token::Whitespace, Span::new(5, 13)),
+            (Token::Identifier("y".to_string()), Span::new(13, 14)),
+            (Token::Whitespace, Span::new(14, 29)),
+            (Token::Operator(Operator::Equal), Span::new(29, 30)),
+            (Token::Whitespace, Span::new(30, 34)),
+            (Token::Integer(2), Span::new(34, 35)),
+        ]
+    );
+
+    // Block comments do not nest, and a comment at the end needs no line break
+    assert_eq!(
+        extract_tokens_filtered("a /* /* */ b // c"),
+        vec![
+            Token::Identifier("a".to_string()),
+            Token::Identifier("b".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let error = extract_tokens_with_spans("a /* b\n*").unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnterminatedComment);
+    assert_eq!(error.span, Span::new(2, 8));
+    assert_eq!(
+        error.to_string(),
+        "Unterminated block comment starting at position 2"
+    );
+}
+
+#[test]
+fn test_conditional_operator() {
+    assert_eq!(
+        extract_tokens_filtered("x ? 1 : 2"),
+        vec![
+            Token::Identifier("x".to_string()),
+            Token::Operator(Operator::Question),
+            Token::Integer(1),
+            Token::Operator(Operator::Colon),
+            Token::Integer(2),
         ]
     );
+
+    // Like every other operator, `?` and `:` must be separated from their operands
+    let error = extract_tokens_with_spans("x?1:2").unwrap_err();
+    assert_eq!(
+        error.kind,
+        LexErrorKind::MissingSeparator {
+            previous: Token::Identifier("x".to_string()),
+            next: Token::Operator(Operator::Question),
+        }
+    );
+    assert_eq!(error.span, Span::new(0, 2));
+}
+
+#[test]
+fn test_range_loop_tokens() {
+    assert_eq!(
+        extract_tokens_filtered("for i in -3 .. 10\nfor j in a ..= b"),
+        vec![
+            Token::Keyword(Keyword::For),
+            Token::Identifier("i".to_string()),
+            Token::Keyword(Keyword::In),
+            Token::Integer(-3),
+            Token::Operator(Operator::Range),
+            Token::Integer(10),
+            Token::Keyword(Keyword::For),
+            Token::Identifier("j".to_string()),
+            Token::Keyword(Keyword::In),
+            Token::Identifier("a".to_string()),
+            Token::Operator(Operator::RangeInclusive),
+            Token::Identifier("b".to_string()),
+        ]
+    );
+
+    // Like every other operator, `..` must be separated from its operands
+    assert_eq!(
+        extract_tokens_with_spans("0..10").unwrap_err().kind,
+        LexErrorKind::MissingSeparator {
+            previous: Token::Integer(0),
+            next: Token::Operator(Operator::Range),
+        }
+    );
+    assert_eq!(
+        extract_tokens_filtered("1.5 .. 2.5"),
+        vec![
+            decimal("1.5"),
+            Token::Operator(Operator::Range),
+            decimal("2.5"),
+        ]
+    );
+}
+
+#[test]
+fn test_array_tokens() {
+    let bracket = |c| Token::Punctuation(parse_punctuation(c).unwrap());
+    let identifier = |name: &str| Token::Identifier(name.to_string());
+
+    // Punctuation needs no whitespace around it
+    assert_eq!(
+        extract_tokens_filtered("xs = [1, 2.5,[]]\nxs[i + 1] = -5"),
+        vec![
+            identifier("xs"),
+            Token::Operator(Operator::Equal),
+            bracket("["),
+            Token::Integer(1),
+            bracket(","),
+            decimal("2.5"),
+            bracket(","),
+            bracket("["),
+            bracket("]"),
+            bracket("]"),
+            identifier("xs"),
+            bracket("["),
+            identifier("i"),
+            Token::Operator(Operator::Plus),
+            Token::Integer(1),
+            bracket("]"),
+            Token::Operator(Operator::Equal),
+            Token::Integer(-5),
+        ]
+    );
+    assert_eq!(Punctuation::Comma.as_str(), ",");
+    assert_eq!(parse_punctuation("("), None);
+
+    // Other tokens still need whitespace between them
+    assert_eq!(
+        extract_tokens_with_spans("[a+1]").unwrap_err().kind,
+        LexErrorKind::MissingSeparator {
+            previous: identifier("a"),
+            next: Token::Operator(Operator::Plus),
+        }
+    );
+}
+
+#[test]
+fn test_decimal_keeps_its_lexeme() {
+    let tokens = extract_tokens_filtered("0.10 0.1 -2.50");
+    let Token::Decimal(padded) = &tokens[0] else {
+        panic!("expected a decimal, got {:?}", tokens[0]);
+    };
+    let Token::Decimal(short) = &tokens[1] else {
+        panic!("expected a decimal, got {:?}", tokens[1]);
+    };
+
+    assert_ne!(tokens[0], tokens[1]);
+    assert_eq!(padded.value(), short.value());
+    assert_eq!(padded.lexeme(), "0.10");
+    assert_eq!(padded.to_string(), "0.10");
+    assert_eq!(tokens[2], decimal("-2.50"));
+    assert_eq!(format!("{:?}", tokens[2]), "Decimal(-2.50)");
+}
+
+#[test]
+fn test_decimal_lit_new() {
+    assert_eq!(DecimalLit::new("1.5").unwrap().value(), 1.5);
+    assert!(DecimalLit::new("1.5.2").is_none());
+    assert!(DecimalLit::new("x").is_none());
+}
+
+#[test]
+fn test_tokens_in_hash_set() {
+    let tokens = extract_tokens(String::from("fn f x = [1, 2.5] \"a\" \"b ${x}\" != y"));
+    let kinds: HashSet<TokenKind> = tokens.iter().map(Token::kind).collect();
+    assert_eq!(kinds.len(), 9, "every token kind should be covered");
+
+    let mut set: HashSet<Token> = tokens.iter().cloned().collect();
+    let distinct = set.len();
+    assert!(set.contains(&Token::Operator(Operator::NotEqual)));
+    assert!(set.contains(&decimal("2.5")));
+
+    // Equal tokens collide, whichever way they were made
+    set.extend(extract_tokens(String::from("x != 2.5")));
+    set.insert(Token::Identifier("f".to_string()));
+    assert_eq!(set.len(), distinct);
+
+    // Decimals are keyed by their text, not their value
+    set.insert(decimal("2.50"));
+    assert_eq!(set.len(), distinct + 1);
+}
+
+#[test]
+fn test_lex_at() {
+    let source = "count = 42 + 3.5\n  while  x";
+
+    let count = Some((Token::Identifier("count".to_string()), Span::new(0, 5)));
+    assert_eq!(lex_at(source, 0), count);
+    assert_eq!(lex_at(source, 4), count);
+    assert_eq!(lex_at(source, 5), None);
+    assert_eq!(
+        lex_at(source, 9),
+        Some((Token::Integer(42), Span::new(8, 10)))
+    );
+    assert_eq!(
+        lex_at(source, 14),
+        Some((decimal("3.5"), Span::new(13, 16)))
+    );
+    assert_eq!(lex_at(source, 18), None);
+    assert_eq!(
+        lex_at(source, 21),
+        Some((Token::Keyword(Keyword::While), Span::new(19, 24)))
+    );
+    assert_eq!(lex_at(source, 25), None);
+    assert_eq!(
+        lex_at(source, source.len() - 1).unwrap().1,
+        Span::new(26, 27)
+    );
+    assert_eq!(lex_at(source, source.len()), None);
+    assert_eq!(lex_at(source, 100), None);
+}
+
+#[test]
+fn test_lex_at_multibyte_character() {
+    let source = "x = área";
+
+    // Byte 5 is the middle of `á`
+    assert_eq!(
+        lex_at(source, 5),
+        Some((Token::Identifier("área".to_string()), Span::new(4, 9)))
+    );
+}
+
+#[test]
+fn test_lex_at_after_error() {
+    // Only the line of the offset is lexed, so the error on the first line is not seen
+    let source = "x = $ 1\ny = 2 $ z";
+
+    assert!(extract_tokens_with_spans(source).is_err());
+    assert_eq!(
+        lex_at(source, 12),
+        Some((Token::Integer(2), Span::new(12, 13)))
+    );
+    // Tokens after an error on their own line are not found
+    assert_eq!(lex_at(source, 16), None);
+    assert_eq!(lex_at(source, 4), None);
 }
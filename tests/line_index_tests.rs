@@ -0,0 +1,108 @@
+use compiler_project_tc3002_b::Span;
+use compiler_project_tc3002_b::line_index::LineIndex;
+
+/// CRLF and LF endings, multi-byte characters, an empty CRLF line and an empty last line
+const SOURCE: &str = "let é = 1\r\nnombre = \"数\"\n\r\nend\n";
+
+#[test]
+fn test_line_index_line_col() {
+    let index = LineIndex::new(SOURCE);
+
+    assert_eq!(index.line_count(), 5);
+    assert_eq!(index.line_col(0), (1, 1));
+    assert_eq!(index.line_col(6), (1, 6));
+    assert_eq!(index.line_col(10), (1, 10));
+    assert_eq!(index.line_col(12), (2, 1));
+    assert_eq!(index.line_col(22), (2, 11));
+    assert_eq!(index.line_col(25), (2, 12));
+    assert_eq!(index.line_col(28), (3, 2));
+    assert_eq!(index.line_col(29), (4, 1));
+    assert_eq!(index.line_col(33), (5, 1));
+}
+
+#[test]
+fn test_line_index_line_col_out_of_range() {
+    let index = LineIndex::new(SOURCE);
+
+    // The middle of `数` refers to `数`, and offsets past the end to the end
+    assert_eq!(index.line_col(23), (2, 11));
+    assert_eq!(index.line_col(100), (5, 1));
+}
+
+#[test]
+fn test_line_index_offset() {
+    let index = LineIndex::new(SOURCE);
+
+    assert_eq!(index.offset(1, 6), Some(6));
+    assert_eq!(index.offset(1, 10), Some(10));
+    assert_eq!(index.offset(2, 11), Some(22));
+    assert_eq!(index.offset(2, 12), Some(25));
+    assert_eq!(index.offset(3, 1), Some(27));
+    assert_eq!(index.offset(5, 1), Some(33));
+
+    assert_eq!(index.offset(1, 11), None);
+    assert_eq!(index.offset(3, 2), None);
+    assert_eq!(index.offset(5, 2), None);
+    assert_eq!(index.offset(6, 1), None);
+    assert_eq!(index.offset(0, 1), None);
+    assert_eq!(index.offset(1, 0), None);
+}
+
+#[test]
+fn test_line_index_round_trip() {
+    let index = LineIndex::new(SOURCE);
+
+    for offset in (0..=SOURCE.len()).filter(|&i| SOURCE.is_char_boundary(i)) {
+        // Only the `\r` of a CRLF ending has no column of its own past the line's text
+        if SOURCE[offset..].starts_with('\n') && SOURCE[..offset].ends_with('\r') {
+            continue;
+        }
+        let (line, column) = index.line_col(offset);
+        assert_eq!(
+            index.offset(line, column),
+            Some(offset),
+            "offset {}",
+            offset
+        );
+    }
+}
+
+#[test]
+fn test_line_index_line_text() {
+    let index = LineIndex::new(SOURCE);
+
+    assert_eq!(index.line_text(1), "let é = 1");
+    assert_eq!(index.line_text(2), "nombre = \"数\"");
+    assert_eq!(index.line_text(3), "");
+    assert_eq!(index.line_text(4), "end");
+    assert_eq!(index.line_text(5), "");
+    assert_eq!(index.line_text(6), "");
+    assert_eq!(index.line_range(2), Some(12..26));
+}
+
+#[test]
+fn test_line_index_without_trailing_newline() {
+    let index = LineIndex::new("a\nbc");
+
+    assert_eq!(index.line_count(), 2);
+    assert_eq!(index.line_text(2), "bc");
+    assert_eq!(index.line_col(4), (2, 3));
+    assert_eq!(index.offset(2, 3), Some(4));
+
+    // A `\r` not followed by `\n` is part of the line
+    assert_eq!(LineIndex::new("a\r").line_text(1), "a\r");
+}
+
+#[test]
+fn test_line_index_column_utf16() {
+    let source = "a = \"😀\" + á\n\tb";
+    let index = LineIndex::new(source);
+
+    // `😀` is one character but two UTF-16 code units, `á` is one of each
+    let plus = source.find('+').unwrap();
+    assert_eq!(index.line_col(plus), (1, 9));
+    assert_eq!(index.column_utf16(plus), 10);
+    assert_eq!(index.column_utf16(source.find('á').unwrap()), 12);
+    assert_eq!(index.column_utf16(source.len()), 3);
+    assert_eq!(Span::new(plus, plus + 1).column_utf16(&index), 10);
+}
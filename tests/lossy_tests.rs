@@ -0,0 +1,177 @@
+use compiler_project_tc3002_b::*;
+
+/// Checks that the items cover the input in order without gaps or overlaps
+fn assert_covers(input: &str, items: &[LexItem]) {
+    let mut end = 0;
+    for item in items {
+        let span = item.span();
+        assert_eq!(span.start, end, "gap or overlap at {} in {:?}", end, input);
+        assert!(span.end > span.start, "empty item in {:?}", input);
+        end = span.end;
+    }
+    assert_eq!(end, input.len(), "input not fully covered: {:?}", input);
+}
+
+#[test]
+fn test_lossy_matches_normal_lexing() {
+    let input = "while x <= 10\n\tx += 1";
+    let items = lex_lossy(input);
+    let expected: Vec<LexItem> = extract_tokens_with_spans(input)
+        .unwrap()
+        .into_iter()
+        .map(|(token, span)| LexItem::Token { token, span })
+        .collect();
+
+    assert_eq!(items, expected);
+}
+
+#[test]
+fn test_lossy_skips_unrecognized_characters() {
+    let items = lex_lossy("x $€ y");
+
+    assert_eq!(
+        items,
+        vec![
+            LexItem::Token {
+                token: Token::Identifier("x".to_string()),
+                span: Span::new(0, 1),
+            },
+            LexItem::Token {
+                token: Token::Whitespace,
+                span: Span::new(1, 2),
+            },
+            LexItem::Error {
+                span: Span::new(2, 3),
+                reason: LexErrorKind::UnrecognizedToken('$'),
+            },
+            LexItem::Error {
+                span: Span::new(3, 6),
+                reason: LexErrorKind::UnrecognizedToken('€'),
+            },
+            LexItem::Token {
+                token: Token::Whitespace,
+                span: Span::new(6, 7),
+            },
+            LexItem::Token {
+                token: Token::Identifier("y".to_string()),
+                span: Span::new(7, 8),
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_lossy_reports_missing_separator_and_continues() {
+    let items = lex_lossy("42+x");
+
+    assert_eq!(
+        items,
+        vec![
+            LexItem::Token {
+                token: Token::Integer(42),
+                span: Span::new(0, 2),
+            },
+            LexItem::Error {
+                span: Span::new(2, 3),
+                reason: LexErrorKind::MissingSeparator {
+                    previous: Token::Integer(42),
+                    next: Token::Operator(Operator::Plus),
+                },
+            },
+            LexItem::Error {
+                span: Span::new(3, 4),
+                reason: LexErrorKind::MissingSeparator {
+                    previous: Token::Operator(Operator::Plus),
+                    next: Token::Identifier("x".to_string()),
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_lossy_oversized_integer() {
+    let input = "x = 99999999999999999999";
+    let items = lex_lossy(input);
+
+    assert_eq!(
+        items.last(),
+        Some(&LexItem::Error {
+            span: Span::new(4, input.len()),
+            reason: LexErrorKind::IntegerOverflow,
+        })
+    );
+    assert_eq!(
+        extract_tokens_with_spans(input).unwrap_err().kind,
+        LexErrorKind::IntegerOverflow
+    );
+}
+
+#[test]
+fn test_lossy_unterminated_comment() {
+    let input = "x // y\nz /* w";
+    let items = lex_lossy(input);
+
+    assert_eq!(
+        items[items.len() - 2..],
+        [
+            LexItem::Token {
+                token: Token::Whitespace,
+                span: Span::new(8, 9),
+            },
+            LexItem::Error {
+                span: Span::new(9, input.len()),
+                reason: LexErrorKind::UnterminatedComment,
+            },
+        ]
+    );
+    assert_covers(input, &items);
+}
+
+/// Small deterministic pseudo-random generator (xorshift64)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+const ALPHABET: &[char] = &[
+    'a', 'z', '_', '0', '9', '.', '-', '+', '*', '/', '%', '=', '<', '>', '!', '&', '|', ' ', '\n',
+    '\t', '\r', '$', '"', '#', 'é', '€', '🦀', '\0', '\\',
+];
+
+#[test]
+fn test_lossy_never_panics_and_covers_random_input() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+    for _ in 0..5000 {
+        let len = rng.below(40);
+        let input: String = (0..len)
+            .map(|_| {
+                if rng.below(8) == 0 {
+                    char::from_u32(rng.below(0x11_0000) as u32).unwrap_or('?')
+                } else {
+                    ALPHABET[rng.below(ALPHABET.len())]
+                }
+            })
+            .collect();
+
+        assert_covers(&input, &lex_lossy(&input));
+    }
+
+    for input in ["\u{feff}", "\u{feff}#!x\ny $", "#!", "#!a\n#!b"] {
+        assert_covers(input, &lex_lossy(input));
+    }
+
+    let long_number = "9".repeat(100);
+    assert_covers(&long_number, &lex_lossy(&long_number));
+}
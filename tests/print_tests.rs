@@ -0,0 +1,129 @@
+use compiler_project_tc3002_b::*;
+use std::io::{self, Write};
+
+#[test]
+fn test_print_tokens_skips_whitespace() {
+    let tokens = extract_tokens(String::from("x = 1.5"));
+    let mut out = Vec::new();
+
+    print_tokens(&tokens, &mut out, &PrintOptions::default()).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\
+Token: Identifier(\"x\")
+Token: Operator(Equal)
+Token: Decimal(1.5)
+"
+    );
+}
+
+#[test]
+fn test_print_tokens_with_whitespace() {
+    let tokens = extract_tokens(String::from("if x"));
+    let mut out = Vec::new();
+    let options = PrintOptions {
+        include_whitespace: true,
+        ..PrintOptions::default()
+    };
+
+    print_tokens(&tokens, &mut out, &options).unwrap();
+
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\
+Token: Keyword(If)
+Token: Whitespace
+Token: Identifier(\"x\")
+"
+    );
+}
+
+/// Writer that accepts a fixed number of bytes and then fails
+struct FailingWriter {
+    written: Vec<u8>,
+    capacity: usize,
+}
+
+impl Write for FailingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written.len() + buf.len() > self.capacity {
+            return Err(io::Error::other("disk full"));
+        }
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_print_tokens_stops_at_write_error() {
+    let tokens = extract_tokens(String::from("a b c"));
+    let mut out = FailingWriter {
+        written: Vec::new(),
+        capacity: 50,
+    };
+
+    let error = print_tokens(&tokens, &mut out, &PrintOptions::default()).unwrap_err();
+
+    assert_eq!(error.to_string(), "disk full");
+    assert_eq!(
+        String::from_utf8(out.written).unwrap(),
+        "Token: Identifier(\"a\")\nToken: Identifier(\"b\")\n"
+    );
+}
+
+#[test]
+fn test_run_returns_tokens() {
+    let path = std::env::temp_dir().join(format!("lexer_run_{}.txt", std::process::id()));
+    std::fs::write(&path, "while x").unwrap();
+
+    let tokens = run(&path.to_string_lossy()).unwrap();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Keyword(Keyword::While),
+            Token::Whitespace,
+            Token::Identifier("x".to_string()),
+        ]
+    );
+
+    std::fs::write(&path, "while$").unwrap();
+    assert!(run(&path.to_string_lossy()).is_err());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_trace_flag_writes_to_stderr() {
+    let path = std::env::temp_dir().join(format!("lexer_trace_{}.txt", std::process::id()));
+    std::fs::write(&path, "x = 12").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg("--trace")
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Token: Identifier(\"x\")\nToken: Operator(Equal)\nToken: Integer(12)\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "\
+0..1 Identifier \"x\"
+1..2 Whitespace \" \"
+2..3 Operator \"=\"
+3..4 Whitespace \" \"
+4..6 Integer \"12\"
+"
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,79 @@
+use compiler_project_tc3002_b::Span;
+use compiler_project_tc3002_b::references::*;
+
+/// `total` is a function name, a parameter and a variable
+const PROGRAM: &str = "\
+fn total total
+    total = total + 1
+fn main
+    x = total 2
+";
+
+fn spans_of(source: &str, name: &str) -> Vec<Span> {
+    source
+        .match_indices(name)
+        .map(|(start, _)| Span::new(start, start + name.len()))
+        .collect()
+}
+
+#[test]
+fn test_occurrences_and_definitions() {
+    let index = identifier_index(PROGRAM).unwrap();
+    let total = spans_of(PROGRAM, "total");
+
+    assert_eq!(index.occurrences("total"), total.as_slice());
+    assert_eq!(index.count("total"), 5);
+    assert_eq!(index.definitions("total"), &total[..1]);
+    assert_eq!(index.uses("total").collect::<Vec<_>>(), &total[1..]);
+    assert_eq!(
+        index.definitions("main"),
+        spans_of(PROGRAM, "main").as_slice()
+    );
+    assert_eq!(index.uses("main").count(), 0);
+    assert_eq!(index.definitions("x"), &[]);
+}
+
+#[test]
+fn test_names_exclude_keywords() {
+    let index = identifier_index(PROGRAM).unwrap();
+
+    let mut names: Vec<&str> = index.names().collect();
+    names.sort();
+    assert_eq!(names, ["main", "total", "x"]);
+    assert_eq!(index.len(), 3);
+    assert!(index.occurrences("fn").is_empty());
+    assert!(index.occurrences("missing").is_empty());
+    assert_eq!(index.count("missing"), 0);
+}
+
+#[test]
+fn test_identifier_index_error() {
+    assert!(identifier_index("x = $").is_err());
+    assert!(identifier_index("").unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_refs() {
+    let path = std::env::temp_dir().join(format!("lexer_refs_{}.lang", std::process::id()));
+    std::fs::write(&path, PROGRAM).unwrap();
+    let run = |name: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .arg("refs")
+            .arg(&path)
+            .arg(name)
+            .output()
+            .unwrap()
+    };
+
+    let output = run("total");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1:4\n1:10\n2:5\n2:13\n4:9\n"
+    );
+
+    let output = run("nothing");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    std::fs::remove_file(path).unwrap();
+}
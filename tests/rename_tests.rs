@@ -0,0 +1,70 @@
+use compiler_project_tc3002_b::rename::*;
+
+#[test]
+fn test_rename_every_occurrence() {
+    let source = "fn total total\n    total = total + 1\nfn main\n    x = total 2\n";
+    assert_eq!(
+        rename_identifier(source, "total", "sum").unwrap(),
+        "fn sum sum\n    sum = sum + 1\nfn main\n    x = sum 2\n"
+    );
+}
+
+#[test]
+fn test_rename_next_to_punctuation_and_operators() {
+    let source = "xs = [count,count] count += count\nxs[count]\n";
+    assert_eq!(
+        rename_identifier(source, "count", "n").unwrap(),
+        "xs = [n,n] n += n\nxs[n]\n"
+    );
+}
+
+#[test]
+fn test_rename_keeps_other_bytes() {
+    let source = "x = \"x\"  // x\n\tx\t=   x_1 /* x */\r\n";
+    let renamed = rename_identifier(source, "x", "value").unwrap();
+
+    assert_eq!(renamed, "value = \"x\"  // x\n\tvalue\t=   x_1 /* x */\r\n");
+    let unchanged = |text: &str| text.replace("value", "x");
+    assert_eq!(unchanged(&renamed), source);
+}
+
+#[test]
+fn test_rename_missing_identifier() {
+    let source = "a = b\n";
+    assert_eq!(rename_identifier(source, "c", "d").unwrap(), source);
+}
+
+#[test]
+fn test_rename_to_keyword() {
+    assert_eq!(
+        rename_identifier("x = 1\n", "x", "while"),
+        Err(RenameError::WouldShadowKeyword("while".to_string()))
+    );
+}
+
+#[test]
+fn test_rename_to_invalid_identifier() {
+    for name in ["", "1x", "a b", "x+", "\"x\""] {
+        assert_eq!(
+            rename_identifier("x = 1\n", "x", name),
+            Err(RenameError::InvalidIdentifier(name.to_string()))
+        );
+    }
+}
+
+#[test]
+fn test_rename_used_in_interpolation() {
+    assert_eq!(
+        rename_identifier("x = 1\nprint \"${x}\"\n", "x", "y"),
+        Err(RenameError::InInterpolation("x".to_string()))
+    );
+    assert!(rename_identifier("x = 1\nprint \"${z}\"\n", "x", "y").is_ok());
+}
+
+#[test]
+fn test_rename_lex_error() {
+    assert!(matches!(
+        rename_identifier("x = @\n", "x", "y"),
+        Err(RenameError::Lex(_))
+    ));
+}
@@ -0,0 +1,110 @@
+use compiler_project_tc3002_b::repl;
+
+/// Runs the REPL on the given input and returns everything it wrote
+fn transcript(input: &str) -> String {
+    let mut output = Vec::new();
+    repl::run(input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_repl_prints_tokens_of_each_line() {
+    assert_eq!(
+        transcript("x = 1\nwhile y\n"),
+        "\
+>> Token: Identifier(\"x\")
+Token: Operator(Equal)
+Token: Integer(1)
+>> Token: Keyword(While)
+Token: Identifier(\"y\")
+>> "
+    );
+}
+
+#[test]
+fn test_repl_shows_errors_and_keeps_going() {
+    assert_eq!(
+        transcript("a $\nb\n"),
+        "\
+>> error: Unrecognized token starting at position 2: '$'
+ --> 1:3
+  |
+1 | a $
+  |   ^
+>> Token: Identifier(\"b\")
+>> "
+    );
+}
+
+#[test]
+fn test_repl_continues_entries() {
+    assert_eq!(
+        transcript("x = 1 +\n2\ns = \"a\nb\"\ny -\n\nz\n"),
+        "\
+>> .. Token: Identifier(\"x\")
+Token: Operator(Equal)
+Token: Integer(1)
+Token: Operator(Plus)
+Token: Integer(2)
+>> .. Token: Identifier(\"s\")
+Token: Operator(Equal)
+Token: StringLiteral(\"a\\nb\")
+>> .. Token: Identifier(\"y\")
+Token: Operator(Minus)
+>> Token: Identifier(\"z\")
+>> "
+    );
+}
+
+#[test]
+fn test_repl_continues_unclosed_brackets() {
+    assert_eq!(
+        transcript("xs = [1,\n[2]\n]\nys = [\n\nz ]\n"),
+        "\
+>> .. .. Token: Identifier(\"xs\")
+Token: Operator(Equal)
+Token: Punctuation(LeftBracket)
+Token: Integer(1)
+Token: Punctuation(Comma)
+Token: Punctuation(LeftBracket)
+Token: Integer(2)
+Token: Punctuation(RightBracket)
+Token: Punctuation(RightBracket)
+>> .. Token: Identifier(\"ys\")
+Token: Operator(Equal)
+Token: Punctuation(LeftBracket)
+>> Token: Identifier(\"z\")
+Token: Punctuation(RightBracket)
+>> "
+    );
+}
+
+#[test]
+fn test_repl_commands() {
+    assert_eq!(
+        transcript(":help\n:eval\n:what\n:quit\nx\n"),
+        "\
+>> Enter code to see its tokens. Commands:
+  :help  show this message
+  :eval  evaluate entries instead (not available yet)
+  :quit  leave the REPL
+>> There is no evaluator yet; entries are only lexed
+>> Unknown command `:what`; try :help
+>> "
+    );
+}
+
+#[test]
+fn test_repl_reports_unfinished_entry_at_end_of_input() {
+    assert_eq!(
+        transcript("s = \"open\n"),
+        "\
+>> .. 
+error: Unterminated string starting at position 4
+ --> 1:5
+  |
+1 | s = \"open
+  |     ^^^^^
+"
+    );
+}
@@ -0,0 +1,180 @@
+use compiler_project_tc3002_b::semantic_cube::*;
+use compiler_project_tc3002_b::{Operator, parse_operator};
+
+const I: Option<Type> = Some(Type::Int);
+const F: Option<Type> = Some(Type::Float);
+const B: Option<Type> = Some(Type::Bool);
+const S: Option<Type> = Some(Type::String);
+const X: Option<Type> = None;
+
+/// Expected `[lhs][rhs]` result types for every operator, rows and columns ordered Int, Float, Bool,
+/// String
+fn expected_cube() -> Vec<(Operator, [[Option<Type>; 4]; 4])> {
+    let addition = [[I, F, X, X], [F, F, X, X], [X, X, X, X], [X, X, X, S]];
+    let arithmetic = [[I, F, X, X], [F, F, X, X], [X, X, X, X], [X, X, X, X]];
+    let compound_addition = [[I, X, X, X], [F, F, X, X], [X, X, X, X], [X, X, X, S]];
+    let compound = [[I, X, X, X], [F, F, X, X], [X, X, X, X], [X, X, X, X]];
+    let equality = [[B, B, X, X], [B, B, X, X], [X, X, B, X], [X, X, X, B]];
+    let ordering = [[B, B, X, X], [B, B, X, X], [X, X, X, X], [X, X, X, B]];
+    let logical = [[X, X, X, X], [X, X, X, X], [X, X, B, X], [X, X, X, X]];
+    let invalid = [[X; 4]; 4];
+
+    vec![
+        (Operator::Plus, addition),
+        (Operator::Minus, arithmetic),
+        (Operator::Multiply, arithmetic),
+        (Operator::Divide, arithmetic),
+        (Operator::PlusEqual, compound_addition),
+        (Operator::MinusEqual, compound),
+        (Operator::MultiplyEqual, compound),
+        (Operator::DivideEqual, compound),
+        (Operator::Modulo, arithmetic),
+        (
+            Operator::Equal,
+            [[I, X, X, X], [F, F, X, X], [X, X, B, X], [X, X, X, S]],
+        ),
+        (Operator::EqualEqual, equality),
+        (Operator::NotEqual, equality),
+        (Operator::Less, ordering),
+        (Operator::LessEqual, ordering),
+        (Operator::Greater, ordering),
+        (Operator::GreaterEqual, ordering),
+        (Operator::And, logical),
+        (Operator::Or, logical),
+        (Operator::Not, invalid),
+        (Operator::Question, invalid),
+        (Operator::Colon, invalid),
+        (Operator::Range, invalid),
+        (Operator::RangeInclusive, invalid),
+    ]
+}
+
+#[test]
+fn test_cube_matches_expected_matrix() {
+    for (op, matrix) in expected_cube() {
+        for (i, lhs) in Type::ALL.into_iter().enumerate() {
+            for (j, rhs) in Type::ALL.into_iter().enumerate() {
+                assert_eq!(
+                    result_type(op, lhs, rhs),
+                    matrix[i][j],
+                    "{:?} {:?} {:?}",
+                    lhs,
+                    op,
+                    rhs
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cube_covers_every_operator() {
+    let symbols = [
+        "+", "+=", "-", "-=", "*", "*=", "/", "/=", "%", "=", "==", "!=", "<", "<=", ">", ">=",
+        "&&", "||", "!", "?", ":", "..", "..=",
+    ];
+    let expected = expected_cube();
+
+    assert_eq!(symbols.len(), expected.len());
+    for symbol in symbols {
+        let op = parse_operator(symbol).unwrap();
+        assert!(
+            expected.iter().any(|(expected_op, _)| *expected_op == op),
+            "{:?} is missing from the expected cube",
+            op
+        );
+    }
+}
+
+#[test]
+fn test_int_float_promotion() {
+    assert_eq!(
+        result_type(Operator::Plus, Type::Int, Type::Float),
+        Some(Type::Float)
+    );
+    assert_eq!(
+        result_type(Operator::Less, Type::Int, Type::Float),
+        Some(Type::Bool)
+    );
+    assert_eq!(result_type(Operator::Plus, Type::Bool, Type::Bool), None);
+}
+
+#[test]
+fn test_string_operations() {
+    assert_eq!(
+        result_type(Operator::Plus, Type::String, Type::String),
+        Some(Type::String)
+    );
+    assert_eq!(
+        result_type(Operator::PlusEqual, Type::String, Type::String),
+        Some(Type::String)
+    );
+    assert_eq!(
+        result_type(Operator::Less, Type::String, Type::String),
+        Some(Type::Bool)
+    );
+    assert_eq!(
+        result_type(Operator::NotEqual, Type::String, Type::String),
+        Some(Type::Bool)
+    );
+    assert_eq!(result_type(Operator::Plus, Type::String, Type::Int), None);
+    assert_eq!(
+        result_type(Operator::Minus, Type::String, Type::String),
+        None
+    );
+    assert_eq!(
+        result_type(Operator::Multiply, Type::String, Type::Int),
+        None
+    );
+    assert_eq!(
+        result_type(Operator::Divide, Type::String, Type::String),
+        None
+    );
+    assert_eq!(unary_result_type(Operator::Minus, Type::String), None);
+}
+
+#[test]
+fn test_unary_result_type() {
+    assert_eq!(
+        unary_result_type(Operator::Minus, Type::Float),
+        Some(Type::Float)
+    );
+    assert_eq!(
+        unary_result_type(Operator::Not, Type::Bool),
+        Some(Type::Bool)
+    );
+    assert_eq!(unary_result_type(Operator::Not, Type::Int), None);
+    assert_eq!(unary_result_type(Operator::Plus, Type::Int), None);
+}
+
+#[test]
+fn test_cast_rules() {
+    // `[value][target]`, rows and columns ordered Int, Float, Bool, String
+    let expected = [
+        [I, F, X, X], // Int
+        [I, F, X, X], // Float
+        [I, X, B, X], // Bool
+        [X, X, X, S], // String
+    ];
+
+    for value in Type::ALL {
+        for target in Type::ALL {
+            assert_eq!(
+                cast_type(value, target),
+                expected[value as usize][target as usize],
+                "{:?} as {:?}",
+                value,
+                target
+            );
+        }
+    }
+}
+
+#[test]
+fn test_type_names() {
+    for ty in Type::ALL {
+        assert_eq!(Type::parse(ty.as_str()), Some(ty));
+    }
+    assert_eq!(Type::parse("float"), Some(Type::Float));
+    assert_eq!(Type::parse("Int"), None);
+}
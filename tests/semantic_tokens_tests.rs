@@ -0,0 +1,116 @@
+use compiler_project_tc3002_b::semantic_tokens::*;
+
+/// Builds a token from its LSP integers, in the order they are sent
+fn token(
+    delta_line: u32,
+    delta_start: u32,
+    length: u32,
+    token_type: u32,
+    modifiers: u32,
+) -> SemanticToken {
+    SemanticToken {
+        delta_line,
+        delta_start,
+        length,
+        token_type,
+        modifiers,
+    }
+}
+
+fn type_index(name: &str) -> u32 {
+    TOKEN_TYPES.iter().position(|t| *t == name).unwrap() as u32
+}
+
+#[test]
+fn test_semantic_tokens_encoding() {
+    let source = "fn double x // twice\n    x * 2.5";
+    let (keyword, operator, number, variable, function, comment) = (
+        type_index("keyword"),
+        type_index("operator"),
+        type_index("number"),
+        type_index("variable"),
+        type_index("function"),
+        type_index("comment"),
+    );
+    assert_eq!(TOKEN_MODIFIERS, ["declaration"]);
+
+    assert_eq!(
+        semantic_tokens(source).unwrap(),
+        vec![
+            token(0, 0, 2, keyword, 0),
+            token(0, 3, 6, function, 1),
+            token(0, 7, 1, variable, 0),
+            token(0, 2, 8, comment, 0),
+            token(1, 4, 1, variable, 0),
+            token(0, 2, 1, operator, 0),
+            token(0, 2, 3, number, 0),
+        ]
+    );
+}
+
+#[test]
+fn test_semantic_tokens_split_multi_line_tokens() {
+    let source = "s = \"🦀\nab\" é\r\n/* c\n */ t";
+    let tokens = semantic_tokens(source).unwrap();
+    let (string, comment, variable) = (
+        type_index("string"),
+        type_index("comment"),
+        type_index("variable"),
+    );
+
+    let positions: Vec<(u32, u32, u32, u32)> = decode_semantic_tokens(&tokens)
+        .into_iter()
+        .map(|t| (t.line, t.start, t.length, t.token_type))
+        .collect();
+
+    // `🦀` takes two UTF-16 code units, `é` one
+    assert_eq!(
+        positions,
+        vec![
+            (0, 0, 1, variable),
+            (0, 2, 1, type_index("operator")),
+            (0, 4, 3, string),
+            (1, 0, 3, string),
+            (1, 4, 1, variable),
+            (2, 0, 4, comment),
+            (3, 0, 3, comment),
+            (3, 4, 1, variable),
+        ]
+    );
+}
+
+#[test]
+fn test_decode_semantic_tokens() {
+    let decoded = decode_semantic_tokens(&[
+        token(2, 5, 1, 0, 0),
+        token(0, 3, 2, 1, 0),
+        token(1, 1, 4, 2, 1),
+    ]);
+
+    assert_eq!(
+        decoded,
+        vec![
+            AbsoluteToken {
+                line: 2,
+                start: 5,
+                length: 1,
+                token_type: 0,
+                modifiers: 0
+            },
+            AbsoluteToken {
+                line: 2,
+                start: 8,
+                length: 2,
+                token_type: 1,
+                modifiers: 0
+            },
+            AbsoluteToken {
+                line: 3,
+                start: 1,
+                length: 4,
+                token_type: 2,
+                modifiers: 1
+            },
+        ]
+    );
+}
@@ -0,0 +1,20 @@
+use compiler_project_tc3002_b::testing::check_snapshot;
+use std::fs;
+use std::path::Path;
+
+/// Checks every `tests/cases/*.src` file against the `.tokens` file next to it
+#[test]
+fn test_snapshot_cases() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/cases");
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "src"))
+        .collect();
+    cases.sort();
+
+    assert!(cases.len() >= 5);
+    for case in cases {
+        check_snapshot(&case, &case.with_extension("tokens"));
+    }
+}
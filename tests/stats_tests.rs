@@ -0,0 +1,65 @@
+use compiler_project_tc3002_b::stats::*;
+use compiler_project_tc3002_b::*;
+
+const PROGRAM: &str = "\
+while count < 10
+    count += 1
+
+if count == 10
+    total = count * 2 + -4
+";
+
+#[test]
+fn test_token_stats_counts() {
+    let stats = source_stats(PROGRAM).unwrap();
+
+    assert_eq!(stats.total, 18);
+    assert_eq!(stats.lines_of_code, Some(4));
+    assert_eq!(stats.kinds.get(&TokenKind::Whitespace), None);
+    assert_eq!(stats.kinds[&TokenKind::Identifier], 5);
+    assert_eq!(stats.kinds[&TokenKind::Keyword], 2);
+    assert_eq!(stats.kinds[&TokenKind::Operator], 6);
+    assert_eq!(stats.kinds[&TokenKind::Integer], 5);
+    assert_eq!(stats.identifiers["count"], 4);
+    assert_eq!(stats.keywords[&Keyword::While], 1);
+    assert_eq!(stats.operators[&Operator::PlusEqual], 1);
+    assert_eq!(
+        stats.integers,
+        Some(IntegerStats {
+            min: -4,
+            max: 10,
+            mean: 3.8,
+        })
+    );
+}
+
+#[test]
+fn test_token_stats_without_integers() {
+    let stats = token_stats(&extract_tokens(String::from("a b a")));
+
+    assert_eq!(stats.total, 3);
+    assert_eq!(stats.integers, None);
+    assert_eq!(stats.lines_of_code, None);
+    assert_eq!(stats.identifiers_by_frequency(), vec![("a", 2), ("b", 1)]);
+}
+
+#[test]
+fn test_token_stats_report() {
+    let stats = source_stats(PROGRAM).unwrap();
+
+    assert_eq!(
+        stats.to_string(),
+        "\
+Tokens: 18
+Lines of code: 4
+  Integer: 5
+  Identifier: 5
+  Keyword: 2
+  Operator: 6
+Identifiers: count (4), total (1)
+Keywords: while (1), if (1)
+Operators: + (1), * (1), += (1), = (1), == (1), < (1)
+Integers: min -4, max 10, mean 3.80
+"
+    );
+}
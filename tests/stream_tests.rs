@@ -0,0 +1,191 @@
+use compiler_project_tc3002_b::stream::*;
+use compiler_project_tc3002_b::*;
+use std::io::{self, BufReader, Read};
+
+/// Lexes the input in one go, keeping the tokens before the first error
+fn lex_all(input: &str) -> Vec<Result<Token, LexError>> {
+    match extract_tokens_with_spans(input) {
+        Ok(tokens) => tokens.into_iter().map(|(token, _)| Ok(token)).collect(),
+        Err(error) => {
+            // Find the tokens before the error by lexing the input lossily
+            let mut items: Vec<_> = lex_lossy(input)
+                .into_iter()
+                .map_while(|item| match item {
+                    LexItem::Token { token, .. } => Some(Ok(token)),
+                    LexItem::Error { .. } => None,
+                })
+                .collect();
+            items.push(Err(error));
+            items
+        }
+    }
+}
+
+/// Checks streaming against lexing in one go for every capacity from 1 to `max_capacity`
+fn check_stream(input: &str, max_capacity: usize) {
+    let expected = lex_all(input);
+    for capacity in 1..=max_capacity {
+        let actual: Vec<_> = lex_reader_with_capacity(input.as_bytes(), capacity).collect();
+        assert_eq!(actual, expected, "{:?} with capacity {}", input, capacity);
+    }
+}
+
+#[test]
+fn test_stream_matches_lexing_in_one_go() {
+    check_stream("while x <= 10\n\tx += 1.25", 12);
+    check_stream("", 4);
+    check_stream("   ", 4);
+}
+
+#[test]
+fn test_stream_tokens_across_boundaries() {
+    // With a capacity of 4, each token starts on, ends on, or straddles a chunk boundary
+    let input = "abcd efghijklm 1234 12.5 -3.75 &&  || <= x";
+    check_stream(input, 16);
+
+    let tokens: Vec<_> = lex_reader_with_capacity(input.as_bytes(), 4)
+        .filter(|token| token.as_ref() != Ok(&Token::Whitespace))
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(tokens, extract_tokens_filtered(input));
+    assert_eq!(tokens[1], Token::Identifier("efghijklm".to_string()));
+    assert_eq!(tokens[3], Token::Decimal(DecimalLit::new("12.5").unwrap()));
+}
+
+#[test]
+fn test_stream_decimal_split_after_dot() {
+    check_stream("x = 1.5", 6);
+    check_stream("x = 12.", 6);
+}
+
+#[test]
+fn test_stream_errors() {
+    check_stream("x = $", 8);
+    check_stream("abcd+efgh", 8);
+    check_stream("ab cd&ef", 8);
+    check_stream("x €", 4);
+}
+
+#[test]
+fn test_stream_multibyte_character_split_across_chunks() {
+    let input = "ab €";
+    let items: Vec<_> = lex_reader_with_capacity(input.as_bytes(), 1).collect();
+
+    assert_eq!(
+        items.last().unwrap().as_ref().unwrap_err().span,
+        Span::new(3, 6)
+    );
+    check_stream(input, 4);
+    check_stream("ab añoé", 4);
+}
+
+#[test]
+fn test_stream_invalid_utf8() {
+    let input: &[u8] = b"x = 1 \xff y";
+    let items: Vec<_> = lex_reader_with_capacity(input, 3).collect();
+
+    assert_eq!(
+        items,
+        vec![
+            Ok(Token::Identifier("x".to_string())),
+            Ok(Token::Whitespace),
+            Ok(Token::Operator(Operator::Equal)),
+            Ok(Token::Whitespace),
+            Ok(Token::Integer(1)),
+            Ok(Token::Whitespace),
+            Err(LexError {
+                kind: LexErrorKind::InvalidUtf8,
+                span: Span::new(6, 7),
+            }),
+        ]
+    );
+}
+
+/// Reader that fails after returning its input
+struct FailingReader<'a>(&'a [u8]);
+
+impl Read for FailingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::from(io::ErrorKind::ConnectionReset));
+        }
+        let n = self.0.read(buf)?;
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_stream_read_error() {
+    let reader = BufReader::with_capacity(2, FailingReader(b"if x"));
+    let items: Vec<_> = lex_reader(reader).collect();
+
+    assert_eq!(
+        items,
+        vec![
+            Ok(Token::Keyword(Keyword::If)),
+            Ok(Token::Whitespace),
+            Ok(Token::Identifier("x".to_string())),
+            Err(LexError {
+                kind: LexErrorKind::Io(io::ErrorKind::ConnectionReset),
+                span: Span::new(4, 4),
+            }),
+        ]
+    );
+}
+
+/// Small deterministic pseudo-random generator (xorshift64)
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+const PIECES: &[&str] = &[
+    "x", "abc", "while", "1", "42", "-7", "3.5", ".", "+", "-", "=", "==", "&", "&&", "|", " ",
+    "  ", "\n", "\t", "é", "$", "[", "]", ",", "..", "//", "/*", "*/",
+];
+
+#[test]
+fn test_stream_matches_on_random_input() {
+    let mut rng = Rng(0x1234_5678_9abc_def1);
+
+    for _ in 0..500 {
+        let pieces = rng.below(12);
+        let input: String = (0..pieces)
+            .map(|_| PIECES[rng.below(PIECES.len())])
+            .collect();
+        check_stream(&input, 6);
+    }
+}
+
+#[test]
+fn test_stream_comments_across_boundaries() {
+    check_stream("x /* c */ y", 12);
+    check_stream("x\n  /* a\n b */ /* c */\t// d\ny", 16);
+    check_stream("x /* never closed", 8);
+    check_stream("x\t// line\n/**/ y", 12);
+}
+
+#[test]
+fn test_stream_comment_longer_than_capacity() {
+    let input = format!("x /*{}*/ y", "c".repeat(70_000));
+    let actual: Vec<_> = lex_reader(input.as_bytes()).collect();
+    assert_eq!(actual, lex_all(&input));
+    assert_eq!(actual.len(), 3);
+}
+
+#[test]
+fn test_stream_skips_bom_and_shebang() {
+    check_stream("\u{feff}#!/usr/bin/env mylang\nx = 1", 8);
+    check_stream("#!a\n#!b", 4);
+    check_stream("abcd #!x", 4);
+}
@@ -0,0 +1,322 @@
+use compiler_project_tc3002_b::*;
+
+fn string(value: &str) -> Token {
+    Token::StringLiteral(value.to_string())
+}
+
+#[test]
+fn test_empty_raw_string() {
+    assert_eq!(
+        extract_tokens_with_spans(r#"r"""#).unwrap(),
+        vec![(string(""), Span::new(0, 3))]
+    );
+}
+
+#[test]
+fn test_raw_string_keeps_backslashes() {
+    let tokens = extract_tokens_filtered(r#"path = r"C:\new\table" r"a\nb""#);
+
+    assert_eq!(tokens[2], string(r"C:\new\table"));
+    assert_eq!(tokens[3], string("a\\nb"));
+    assert_eq!(
+        tokens[3],
+        Token::StringLiteral(['a', '\\', 'n', 'b'].iter().collect())
+    );
+}
+
+#[test]
+fn test_hashed_raw_string() {
+    let input = r###"r#"she said "hi""# r##"a "# b"##"###;
+    let tokens = extract_tokens_with_spans(input).unwrap();
+
+    assert_eq!(tokens[0], (string(r#"she said "hi""#), Span::new(0, 18)));
+    assert_eq!(
+        tokens[2],
+        (string(r##"a "# b"##), Span::new(19, input.len()))
+    );
+}
+
+#[test]
+fn test_unterminated_raw_string() {
+    let error = extract_tokens_with_spans("x = r\"abc\ny = 1").unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+    assert_eq!(error.span.start, 4);
+    assert_eq!(
+        error.to_string(),
+        "Unterminated string starting at position 4"
+    );
+}
+
+#[test]
+fn test_mismatched_hash_counts() {
+    // Fewer closing hashes than opening ones never ends the string
+    let error = extract_tokens_with_spans(r###"r##"x"# + 1"###).unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+    assert_eq!(error.span.start, 0);
+
+    // Extra closing hashes are left over after the string
+    let error = extract_tokens_with_spans(r###"r#"x"## + 1"###).unwrap_err();
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('#'));
+    assert_eq!(error.span, Span::new(6, 7));
+}
+
+#[test]
+fn test_r_without_quote_is_an_identifier() {
+    assert_eq!(
+        extract_tokens_filtered("r rx r2"),
+        vec![
+            Token::Identifier("r".to_string()),
+            Token::Identifier("rx".to_string()),
+            Token::Identifier("r2".to_string()),
+        ]
+    );
+    assert_eq!(
+        extract_tokens_with_spans("r#x").unwrap_err().kind,
+        LexErrorKind::UnrecognizedToken('#')
+    );
+}
+
+#[test]
+fn test_raw_string_in_stream() {
+    let input = r####"x = r###"a "## b"### + r"c""####;
+    let expected: Vec<_> = extract_tokens(input.to_string())
+        .into_iter()
+        .map(Ok)
+        .collect();
+
+    for capacity in 1..8 {
+        let tokens: Vec<_> = stream::lex_reader_with_capacity(input.as_bytes(), capacity).collect();
+        assert_eq!(tokens, expected, "capacity {}", capacity);
+    }
+}
+
+#[test]
+fn test_string_with_escapes() {
+    assert_eq!(
+        extract_tokens_filtered(r#""plain" "tab\there" "quote \" and \\""#),
+        vec![
+            string("plain"),
+            string("tab\there"),
+            string("quote \" and \\"),
+        ]
+    );
+}
+
+#[test]
+fn test_multi_line_string() {
+    let input = "msg = \"first\nsecond\nthird\"\nx = 1";
+    let tokens = extract_tokens_with_options(
+        input,
+        &LexerOptions {
+            emit_whitespace: false,
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        tokens[2],
+        (string("first\nsecond\nthird"), Span::new(6, 26))
+    );
+    // Tokens after the string are on the line after its closing quote
+    assert_eq!(diagnostic::line_col(input, tokens[3].1.start), (4, 1));
+}
+
+#[test]
+fn test_string_ending_at_end_of_input() {
+    assert_eq!(
+        extract_tokens_with_spans("\"a\nb\"").unwrap(),
+        vec![(string("a\nb"), Span::new(0, 5))]
+    );
+}
+
+#[test]
+fn test_line_continuation() {
+    let indented = "\"one \\\n        two\"";
+    let unindented = "\"one \\\ntwo\"";
+    let crlf = "\"one \\\r\n\ttwo\"";
+
+    assert_eq!(extract_tokens_filtered(indented), vec![string("one two")]);
+    assert_eq!(extract_tokens_filtered(unindented), vec![string("one two")]);
+    assert_eq!(extract_tokens_filtered(crlf), vec![string("one two")]);
+}
+
+#[test]
+fn test_unterminated_multi_line_string_points_at_quote() {
+    let source = "x = 1\ny = \"open\nstill open\n";
+    let error = extract_tokens_with_spans(source).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnterminatedString);
+    assert_eq!(
+        diagnostic::render(&error.into(), source),
+        "\
+error: Unterminated string starting at position 10
+ --> 2:5
+  |
+2 | y = \"open
+  |     ^^^^^
+"
+    );
+}
+
+#[test]
+fn test_invalid_escape() {
+    let error = extract_tokens_with_spans(r#"x = "a\qb""#).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::InvalidEscape('q'));
+    assert_eq!(error.span, Span::new(4, 8));
+}
+
+#[test]
+fn test_string_in_stream() {
+    let input = "s = \"ab\\\n   cd\\n\" + \"x\ny\"";
+    let expected: Vec<_> = extract_tokens(input.to_string())
+        .into_iter()
+        .map(Ok)
+        .collect();
+
+    for capacity in 1..8 {
+        let tokens: Vec<_> = stream::lex_reader_with_capacity(input.as_bytes(), capacity).collect();
+        assert_eq!(tokens, expected, "capacity {}", capacity);
+    }
+}
+
+fn ident(name: &str) -> Token {
+    Token::Identifier(name.to_string())
+}
+
+#[test]
+fn test_interpolated_string() {
+    let tokens = extract_tokens_filtered(r#""a ${x} b ${y + 1} c""#);
+
+    assert_eq!(
+        tokens,
+        vec![Token::InterpolatedString(vec![
+            StringPart::Literal("a ".to_string()),
+            StringPart::Tokens(vec![ident("x")]),
+            StringPart::Literal(" b ".to_string()),
+            StringPart::Tokens(vec![
+                ident("y"),
+                Token::Operator(Operator::Plus),
+                Token::Integer(1),
+            ]),
+            StringPart::Literal(" c".to_string()),
+        ])]
+    );
+}
+
+#[test]
+fn test_adjacent_interpolations() {
+    assert_eq!(
+        extract_tokens_filtered(r#""${x}${y}""#),
+        vec![Token::InterpolatedString(vec![
+            StringPart::Tokens(vec![ident("x")]),
+            StringPart::Tokens(vec![ident("y")]),
+        ])]
+    );
+}
+
+#[test]
+fn test_escaped_interpolation() {
+    assert_eq!(
+        extract_tokens_filtered(r#""cost: \${x} $5""#),
+        vec![string("cost: ${x} $5")]
+    );
+}
+
+#[test]
+fn test_nested_string_in_interpolation() {
+    assert_eq!(
+        extract_tokens_filtered(r#""${f "}" r"{"}!""#),
+        vec![Token::InterpolatedString(vec![
+            StringPart::Tokens(vec![ident("f"), string("}"), string("{")]),
+            StringPart::Literal("!".to_string()),
+        ])]
+    );
+}
+
+#[test]
+fn test_nested_braces_in_interpolation() {
+    // The braces are matched, then lexing the expression rejects them as usual
+    let error = extract_tokens_with_spans(r#"x = "${ {a} }""#).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('{'));
+    assert_eq!(error.span, Span::new(8, 9));
+}
+
+#[test]
+fn test_unterminated_interpolation() {
+    let error = extract_tokens_with_spans(r#"s = "a ${x"#).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnterminatedInterpolation);
+    assert_eq!(error.span.start, 7);
+}
+
+#[test]
+fn test_empty_interpolation() {
+    let error = extract_tokens_with_spans(r#"s = "a ${ } b""#).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::EmptyInterpolation);
+    assert_eq!(error.span, Span::new(7, 11));
+}
+
+#[test]
+fn test_error_inside_interpolation() {
+    let source = r#"s = "n: ${1 $}""#;
+    let error = extract_tokens_with_spans(source).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::UnrecognizedToken('$'));
+    assert_eq!(error.span, Span::new(12, 13));
+    assert_eq!(
+        extract_tokens_with_spans(r#""${a+1}""#).unwrap_err().span,
+        Span::new(3, 5)
+    );
+}
+
+/// Returns a string with `depth` interpolations nested inside each other, around `1`
+fn nested_interpolations(depth: usize) -> String {
+    format!("x = {}1{}", "\"${".repeat(depth), "}\"".repeat(depth))
+}
+
+#[test]
+fn test_deepest_allowed_interpolation() {
+    let tokens = extract_tokens_filtered(&nested_interpolations(MAX_INTERPOLATION_DEPTH));
+
+    let mut token = &tokens[2];
+    for _ in 0..MAX_INTERPOLATION_DEPTH {
+        let Token::InterpolatedString(parts) = token else {
+            panic!("expected an interpolated string, got {:?}", token);
+        };
+        let [StringPart::Tokens(inner)] = parts.as_slice() else {
+            panic!("expected a single interpolation, got {:?}", parts);
+        };
+        token = &inner[0];
+    }
+    assert_eq!(*token, Token::Integer(1));
+}
+
+#[test]
+fn test_interpolation_too_deep() {
+    let source = nested_interpolations(MAX_INTERPOLATION_DEPTH + 1);
+    let error = extract_tokens_with_spans(&source).unwrap_err();
+
+    assert_eq!(error.kind, LexErrorKind::InterpolationTooDeep);
+    let start = 4 + 3 * MAX_INTERPOLATION_DEPTH + 1;
+    assert_eq!(error.span, Span::new(start, start + 2));
+    assert_eq!(error.kind.code(), "L0012");
+}
+
+#[test]
+fn test_very_deep_interpolation_does_not_overflow_the_stack() {
+    let source = nested_interpolations(100_000);
+
+    // A small stack shows the depth of recursion does not grow with the input
+    let error = std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(move || extract_tokens_with_spans(&source).unwrap_err())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    assert_eq!(error.kind, LexErrorKind::InterpolationTooDeep);
+}
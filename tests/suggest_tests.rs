@@ -0,0 +1,104 @@
+use compiler_project_tc3002_b::suggest::*;
+use compiler_project_tc3002_b::*;
+
+fn suggestions(input: &str) -> Vec<(String, &'static str)> {
+    let tokens = extract_tokens_with_spans(input).unwrap();
+    suggest_keywords(&tokens)
+        .into_iter()
+        .map(|suggestion| (suggestion.identifier, suggestion.keyword))
+        .collect()
+}
+
+#[test]
+fn test_misspelled_keywords() {
+    assert_eq!(
+        suggestions("whlie fpr esle fro fore whle els"),
+        vec![
+            ("whlie".to_string(), "while"),
+            ("fpr".to_string(), "for"),
+            ("esle".to_string(), "else"),
+            ("fro".to_string(), "for"),
+            ("fore".to_string(), "for"),
+            ("whle".to_string(), "while"),
+            ("els".to_string(), "else"),
+        ]
+    );
+}
+
+#[test]
+fn test_suggestion_span() {
+    let tokens = extract_tokens_with_spans("x = 1\nwhlie x").unwrap();
+
+    assert_eq!(
+        suggest_keywords(&tokens),
+        vec![Suggestion {
+            span: Span::new(6, 11),
+            identifier: "whlie".to_string(),
+            keyword: "while",
+        }]
+    );
+}
+
+#[test]
+fn test_legitimate_identifiers() {
+    assert_eq!(
+        suggestions("i x2 f el total count index value _ fnord elif2 form four"),
+        vec![]
+    );
+}
+
+#[test]
+fn test_type_after_as() {
+    assert_eq!(suggestions("x as whlie"), vec![]);
+    assert_eq!(
+        suggestions("esle as esle"),
+        vec![("esle".to_string(), "else")]
+    );
+}
+
+#[test]
+fn test_short_keywords_not_suggested() {
+    assert_eq!(suggestions("int min bin win fun has was iff ass"), vec![]);
+}
+
+#[test]
+fn test_threshold_grows_with_length() {
+    // Two edits are only accepted for longer identifiers
+    assert_eq!(suggestions("wihle"), vec![("wihle".to_string(), "while")]);
+    assert_eq!(suggestions("whle_"), vec![]);
+    assert_eq!(
+        suggestions("whiile_"),
+        vec![("whiile_".to_string(), "while")]
+    );
+    assert_eq!(suggestions("WHILE"), vec![("WHILE".to_string(), "while")]);
+}
+
+#[test]
+fn test_cli_prints_warnings() {
+    let path = std::env::temp_dir().join(format!("lexer_suggest_{}.txt", std::process::id()));
+    std::fs::write(&path, "whlie x").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "\
+warning: `whlie` looks like a misspelling of the keyword `while`
+ --> 1:1
+  |
+1 | whlie x
+  | ^^^^^
+"
+    );
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .starts_with("Token: Identifier(\"whlie\")")
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,97 @@
+use compiler_project_tc3002_b::extract_tokens_with_spans;
+use compiler_project_tc3002_b::table::*;
+
+const PROGRAM: &str = "\
+fn área x
+    total = x * 0.1
+    s = \"a string long enough to be cut short\"
+    while total >= 2.5 [s, 1]
+";
+
+fn table(source: &str, options: &TableOptions) -> String {
+    format_table(source, &extract_tokens_with_spans(source).unwrap(), options)
+}
+
+#[test]
+fn test_table_snapshot() {
+    let expected = "\
+00 1:1  Keyword       fn
+01 1:4  Identifier    área
+02 1:9  Identifier    x
+03 2:5  Identifier    total
+04 2:11 Operator      =
+05 2:13 Identifier    x
+06 2:15 Operator      *
+07 2:17 Decimal       0.1
+08 3:5  Identifier    s
+09 3:7  Operator      =
+10 3:9  StringLiteral \"a string long enough…
+11 4:5  Keyword       while
+12 4:11 Identifier    total
+13 4:17 Operator      >=
+14 4:20 Decimal       2.5
+15 4:24 Punctuation   [
+16 4:25 Identifier    s
+17 4:26 Punctuation   ,
+18 4:28 Integer       1
+19 4:29 Punctuation   ]
+";
+    let options = TableOptions {
+        max_lexeme_width: 22,
+        ..TableOptions::default()
+    };
+
+    assert_eq!(table(PROGRAM, &options), expected);
+}
+
+#[test]
+fn test_table_escapes_whitespace() {
+    let expected = "\
+0 1:1 Identifier x
+1 1:2 Whitespace \\r\\n\\t
+2 2:2 Identifier y
+";
+    let options = TableOptions {
+        include_whitespace: true,
+        ..TableOptions::default()
+    };
+
+    assert_eq!(table("x\r\n\ty", &options), expected);
+}
+
+#[test]
+fn test_table_truncation_width() {
+    let source = "abcdef abc";
+
+    let options = |max_lexeme_width| TableOptions {
+        max_lexeme_width,
+        ..TableOptions::default()
+    };
+    assert_eq!(
+        table(source, &options(3)),
+        "0 1:1 Identifier ab…\n1 1:8 Identifier abc\n"
+    );
+    assert_eq!(
+        table(source, &options(1)),
+        "0 1:1 Identifier …\n1 1:8 Identifier …\n"
+    );
+    assert_eq!(table("", &TableOptions::default()), "");
+}
+
+#[test]
+fn test_cli_table_format() {
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+        .args(["tests/test.txt", "--format", "table"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let first = stdout.lines().next().unwrap();
+    assert!(first.starts_with("0") && first.ends_with(" x"), "{}", first);
+    let widths: Vec<usize> = stdout
+        .lines()
+        .map(|line| line.rfind(' ').unwrap())
+        .collect();
+    assert!(widths.windows(2).all(|pair| pair[0] == pair[1]));
+}
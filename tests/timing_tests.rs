@@ -0,0 +1,80 @@
+use compiler_project_tc3002_b::timing::*;
+use compiler_project_tc3002_b::{Token, run, run_timed};
+use std::process::Command;
+use std::time::Duration;
+
+const INPUT: &str = "tests/test.txt";
+
+#[test]
+fn test_run_timed_records_each_phase_once() {
+    let (tokens, timings) = run_timed(INPUT);
+    let tokens = tokens.unwrap();
+
+    let phases: Vec<Phase> = timings.phases().iter().map(|timing| timing.phase).collect();
+    assert_eq!(phases, vec![Phase::Read, Phase::Lex]);
+    assert_eq!(
+        timings.get(Phase::Read).unwrap().size,
+        std::fs::read_to_string(INPUT).unwrap().len()
+    );
+    assert_eq!(timings.get(Phase::Lex).unwrap().size, tokens.len());
+    assert_eq!(tokens, run(INPUT).unwrap());
+}
+
+#[test]
+fn test_time_phase_without_timings() {
+    let mut timings = PhaseTimings::new();
+
+    assert_eq!(time_phase(None, Phase::Lex, || 3, |_| 1), 3);
+    assert_eq!(
+        time_phase(
+            Some(&mut timings),
+            Phase::Lex,
+            || vec![Token::Whitespace; 2],
+            Vec::len
+        ),
+        vec![Token::Whitespace; 2]
+    );
+    assert_eq!(timings.phases().len(), 1);
+    assert_eq!(timings.phases()[0].size, 2);
+}
+
+#[test]
+fn test_timings_table() {
+    let mut timings = PhaseTimings::new();
+    timings.time(
+        Phase::Read,
+        || std::thread::sleep(Duration::from_millis(1)),
+        |_| 10,
+    );
+    timings.time(Phase::Lex, || (), |_| 4);
+
+    let table = timings.to_string();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1].starts_with("read") && lines[1].ends_with(" 10 bytes"));
+    assert!(lines[2].starts_with("lex") && lines[2].ends_with(" 4 tokens"));
+    assert!(lines[3].starts_with("total"));
+    assert!(timings.total() >= Duration::from_millis(1));
+}
+
+#[test]
+fn test_cli_time_keeps_stdout() {
+    let run_cli = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_compiler_project_tc3002_b"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let plain = run_cli(&[INPUT]);
+    let timed = run_cli(&[INPUT, "--time"]);
+
+    assert!(timed.status.success());
+    assert_eq!(timed.stdout, plain.stdout);
+    let stderr = String::from_utf8(timed.stderr).unwrap();
+    let phases: Vec<&str> = stderr
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+    assert_eq!(phases, ["phase", "read", "lex", "total"]);
+}
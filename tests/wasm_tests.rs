@@ -0,0 +1,33 @@
+//! Run with `wasm-pack test --node -- --features wasm`
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use compiler_project_tc3002_b::wasm::*;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_lex_to_json_valid_program() {
+    assert_eq!(
+        lex_to_json("while x\n  x -= 1"),
+        "{\"tokens\": [\
+         {\"kind\": \"Keyword\", \"text\": \"while\", \"start\": 0, \"end\": 5}, \
+         {\"kind\": \"Identifier\", \"text\": \"x\", \"start\": 6, \"end\": 7}, \
+         {\"kind\": \"Identifier\", \"text\": \"x\", \"start\": 10, \"end\": 11}, \
+         {\"kind\": \"Operator\", \"text\": \"-=\", \"start\": 12, \"end\": 14}, \
+         {\"kind\": \"Integer\", \"text\": \"1\", \"start\": 15, \"end\": 16}]}"
+    );
+    assert_eq!(
+        highlight("x + 1"),
+        "<span class=\"ident\">x</span> <span class=\"op\">+</span> <span class=\"num\">1</span>"
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_lex_to_json_error() {
+    assert_eq!(
+        lex_to_json("x = $"),
+        "{\"error\": {\"code\": \"L0001\", \
+         \"message\": \"Unrecognized token starting at position 4: '$'\", \
+         \"start\": 4, \"end\": 5}}"
+    );
+    assert_eq!(highlight("a < $"), "a &lt; $");
+}